@@ -0,0 +1,296 @@
+use crate::fonts::enumerate::invalidate_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::library::{import_font, library_dir, LibraryFile};
+use crate::fonts::persist::{app_data_dir, read_json, write_json};
+use crate::fonts::resolve_registry_path;
+use crate::win::broadcast_font_change;
+use crate::win::registry::{
+    get_font_registry_value, list_font_registry_values, register_font, remove_font_registry_entry, FontHive,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const SETS_FILE: &str = "activation_sets.json";
+
+// Serialises read-modify-write cycles on the sets file
+static SETS_LOCK: Mutex<()> = Mutex::new(());
+
+// A named group of library fonts that is switched on and off as a whole.
+// Active fonts are registered per user straight from the library; nothing is copied or deleted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationSet {
+    pub name: String,
+    pub fonts: Vec<LibraryFile>,
+    pub active: bool,
+    // Unix timestamp in seconds
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct ActivationSets {
+    sets: Vec<ActivationSet>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationResult {
+    pub set: ActivationSet,
+    // Fonts that couldn't be imported, registered or unregistered; the rest of the set still applies
+    pub errors: Vec<FontError>,
+}
+
+// A library font Windows currently sees, and the sets that want it active
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveFont {
+    pub path: String,
+    pub registry_value_name: String,
+    pub family: Option<String>,
+    pub sets: Vec<String>,
+}
+
+#[tauri::command]
+pub fn list_activation_sets(app: AppHandle) -> Result<Vec<ActivationSet>, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    Ok(load(&app)?.sets)
+}
+
+// Imports the files into the library and stores them as a new, inactive set
+#[tauri::command]
+pub async fn create_activation_set(
+    app: AppHandle,
+    name: String,
+    paths: Vec<String>,
+) -> Result<ActivationResult, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    let mut store = load(&app)?;
+    if store.sets.iter().any(|s| s.name == name) {
+        return Err(FontError::new(
+            FontErrorKind::AlreadyExists,
+            None,
+            format!("An activation set named {} already exists", name),
+        ));
+    }
+
+    let (fonts, errors) = import_all(&app, &paths);
+    let set = ActivationSet {
+        name,
+        fonts,
+        active: false,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+    };
+    store.sets.push(set.clone());
+    invalidate_variants(&app);
+    save(&app, &store)?;
+    Ok(ActivationResult { set, errors })
+}
+
+// Adds fonts to an existing set; if the set is active they are activated right away
+#[tauri::command]
+pub async fn add_fonts_to_set(app: AppHandle, name: String, paths: Vec<String>) -> Result<ActivationResult, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    let mut store = load(&app)?;
+    let index = find_set(&store, &name)?;
+
+    let (fonts, mut errors) = import_all(&app, &paths);
+    let set = &mut store.sets[index];
+    for font in fonts {
+        if set.fonts.iter().any(|f| f.hash == font.hash) {
+            continue;
+        }
+        if set.active {
+            if let Err(error) = activate_font(&font) {
+                errors.push(error);
+            }
+        }
+        set.fonts.push(font);
+    }
+
+    let set = set.clone();
+    invalidate_variants(&app);
+    save(&app, &store)?;
+    if set.active {
+        broadcast_font_change();
+    }
+    Ok(ActivationResult { set, errors })
+}
+
+#[tauri::command]
+pub fn set_activation_set_active(app: AppHandle, name: String, active: bool) -> Result<ActivationResult, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    let mut store = load(&app)?;
+    let index = find_set(&store, &name)?;
+
+    let errors = if active {
+        store.sets[index]
+            .fonts
+            .iter()
+            .filter_map(|font| activate_font(font).err())
+            .collect()
+    } else {
+        deactivate(&store, index)
+    };
+    store.sets[index].active = active;
+
+    let set = store.sets[index].clone();
+    invalidate_variants(&app);
+    save(&app, &store)?;
+    broadcast_font_change();
+    Ok(ActivationResult { set, errors })
+}
+
+// Deactivates the set if needed and forgets it; its files stay in the library
+#[tauri::command]
+pub fn delete_activation_set(app: AppHandle, name: String) -> Result<Vec<FontError>, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    let mut store = load(&app)?;
+    let index = find_set(&store, &name)?;
+
+    let errors = if store.sets[index].active {
+        let errors = deactivate(&store, index);
+        broadcast_font_change();
+        errors
+    } else {
+        Vec::new()
+    };
+    store.sets.remove(index);
+    invalidate_variants(&app);
+    save(&app, &store)?;
+    Ok(errors)
+}
+
+#[tauri::command]
+pub fn list_active_fonts(app: AppHandle) -> Result<Vec<ActiveFont>, FontError> {
+    let _guard = SETS_LOCK.lock().unwrap();
+    let store = load(&app)?;
+    let dir = library_dir(&app)?;
+
+    // Library file path -> (family, active sets containing it)
+    let mut owners: HashMap<String, (String, Vec<String>)> = HashMap::new();
+    for set in store.sets.iter().filter(|s| s.active) {
+        for font in &set.fonts {
+            owners
+                .entry(font.path.to_lowercase())
+                .or_insert_with(|| (font.family.clone(), Vec::new()))
+                .1
+                .push(set.name.clone());
+        }
+    }
+
+    let values = list_font_registry_values(FontHive::CurrentUser)
+        .map_err(|e| FontError::registry(None, "Failed to read HKCU fonts", &e))?;
+    Ok(values
+        .into_iter()
+        .filter_map(|(name, data)| {
+            let path = resolve_registry_path(&data);
+            if !path.starts_with(&dir) {
+                return None;
+            }
+            let owner = owners.get(&path.to_string_lossy().to_lowercase());
+            Some(ActiveFont {
+                path: path.to_string_lossy().to_string(),
+                registry_value_name: name,
+                family: owner.map(|(family, _)| family.clone()),
+                sets: owner.map(|(_, sets)| sets.clone()).unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+// Library hash -> names of the sets containing it
+pub type SetsByHash = HashMap<String, Vec<String>>;
+
+// The guard keeps the sets from changing while the caller acts on the answer; take it before
+// the catalog lock, as set imports do
+pub fn sets_by_hash(app: &AppHandle) -> Result<(MutexGuard<'static, ()>, SetsByHash), FontError> {
+    let guard = SETS_LOCK.lock().unwrap();
+    let mut sets = SetsByHash::new();
+    for set in load(app)?.sets {
+        for font in set.fonts {
+            sets.entry(font.hash).or_default().push(set.name.clone());
+        }
+    }
+    Ok((guard, sets))
+}
+
+fn import_all(app: &AppHandle, paths: &[String]) -> (Vec<LibraryFile>, Vec<FontError>) {
+    let mut fonts: Vec<LibraryFile> = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match import_font(app, Path::new(path)) {
+            Ok(font) if fonts.iter().any(|f| f.hash == font.hash) => {}
+            Ok(font) => fonts.push(font),
+            Err(error) => errors.push(error),
+        }
+    }
+    (fonts, errors)
+}
+
+// Registers the library file unless some value already points at it
+fn activate_font(font: &LibraryFile) -> Result<(), FontError> {
+    let path = Path::new(&font.path);
+    let values = list_font_registry_values(FontHive::CurrentUser)
+        .map_err(|e| FontError::registry(Some(path), "Failed to read HKCU fonts", &e))?;
+    if values.iter().any(|(_, data)| data.eq_ignore_ascii_case(&font.path)) {
+        return Ok(());
+    }
+
+    let mut name = format!("{} (TrueType)", font.family);
+    let taken = get_font_registry_value(FontHive::CurrentUser, &name)
+        .map_err(|e| FontError::registry(Some(path), "Failed to read registry value", &e))?;
+    if taken.is_some() {
+        // Another install of the same family keeps its value
+        name = format!("{} [{}] (TrueType)", font.family, &font.hash[..8]);
+    }
+    register_font(FontHive::CurrentUser, &name, &font.path)
+        .map_err(|e| FontError::registry(Some(path), "Failed to register font", &e))
+}
+
+// Unregisters the set's fonts that no other active set still needs
+fn deactivate(store: &ActivationSets, index: usize) -> Vec<FontError> {
+    let still_needed = |hash: &str| {
+        store
+            .sets
+            .iter()
+            .enumerate()
+            .any(|(i, s)| i != index && s.active && s.fonts.iter().any(|f| f.hash == hash))
+    };
+
+    store.sets[index]
+        .fonts
+        .iter()
+        .filter(|font| !still_needed(&font.hash))
+        .filter_map(|font| {
+            remove_font_registry_entry(&font.path)
+                .err()
+                .map(|e| FontError::registry(Some(Path::new(&font.path)), "Failed to unregister font", &e))
+        })
+        .collect()
+}
+
+fn find_set(store: &ActivationSets, name: &str) -> Result<usize, FontError> {
+    store.sets.iter().position(|s| s.name == name).ok_or_else(|| {
+        FontError::new(
+            FontErrorKind::NotFound,
+            None,
+            format!("No activation set named {}", name),
+        )
+    })
+}
+
+fn sets_file(app: &AppHandle) -> Result<PathBuf, FontError> {
+    Ok(app_data_dir(app)?.join(SETS_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<ActivationSets, FontError> {
+    read_json(&sets_file(app)?)
+}
+
+fn save(app: &AppHandle, store: &ActivationSets) -> Result<(), FontError> {
+    write_json(&sets_file(app)?, store)
+}
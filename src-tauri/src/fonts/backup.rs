@@ -0,0 +1,318 @@
+use crate::fonts::enumerate::installed_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::install::{
+    begin_batch, calculate_file_hash, is_font_registry_name, run_batch, same_path, InstallOptions, InstallResult,
+};
+use crate::fonts::organize::{merge_tags, organizer};
+use crate::fonts::persist::app_data_dir;
+use crate::fonts::plan::{build_plan, PlannedAction};
+use crate::fonts::{resolve_registry_path, FontScope};
+use crate::win::registry::get_font_registry_value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+// Describes the archive's fonts: manifest.json next to fonts/<n>/<file name>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub manifest_version: u32,
+    // Unix timestamp in seconds
+    pub created_at: u64,
+    pub computer_name: Option<String>,
+    pub fonts: Vec<BackupFont>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFont {
+    pub archive_path: String,
+    pub file_name: String,
+    pub original_path: String,
+    // SHA-256, checked on restore
+    pub hash: String,
+    // HKCU Fonts values that registered the file, e.g. "Inter Bold (TrueType)"
+    pub registry_value_names: Vec<String>,
+    pub families: Vec<String>,
+    // Font identity -> tags, for the faces in this file that had any
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub path: String,
+    pub font_count: usize,
+    // Files that couldn't be read; the archive holds everything else
+    pub errors: Vec<FontError>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub install: InstallResult,
+    // Fonts whose tags were put back
+    pub tagged_fonts: usize,
+    // Archive entries that were missing or didn't match their hash, which weren't installed,
+    // and a failure to save the tags
+    pub errors: Vec<FontError>,
+}
+
+// Packs every per-user installed font into a zip archive. Fonts activated from the app library
+// are registered in HKCU from the library folder, which installed_variants loads as well, so they
+// are included too and come back as ordinary per-user installs.
+#[tauri::command]
+pub async fn backup_user_fonts(app: AppHandle, path: String) -> Result<BackupResult, FontError> {
+    let org = organizer(&app);
+
+    // One entry per file; collections list several faces
+    let mut files: BTreeMap<String, BackupFont> = BTreeMap::new();
+    for v in installed_variants() {
+        if v.scope != FontScope::PerUser {
+            continue;
+        }
+        let font_tags = org.tags_for(&v);
+        let index = files.len();
+        let font = files.entry(v.path.to_lowercase()).or_insert_with(|| {
+            let file_name = Path::new(&v.path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            BackupFont {
+                // Numbered folders keep same-named files from different directories apart
+                archive_path: format!("fonts/{}/{}", index, file_name),
+                file_name,
+                original_path: v.path.clone(),
+                hash: String::new(),
+                registry_value_names: Vec::new(),
+                families: Vec::new(),
+                tags: BTreeMap::new(),
+            }
+        });
+        if let Some(name) = v.registry_value_name {
+            if !font.registry_value_names.contains(&name) {
+                font.registry_value_names.push(name);
+            }
+        }
+        if !font.families.contains(&v.family) {
+            font.families.push(v.family);
+        }
+        if let Some(font_tags) = font_tags {
+            font.tags.insert(v.identity, font_tags.clone());
+        }
+    }
+
+    let dest = Path::new(&path);
+    // Written next to the target and renamed over it, so a failed backup never replaces a good one
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dest.with_file_name(format!("{}.{:x}.partial", file_name, nanos));
+    let file = File::create(&tmp).map_err(|e| FontError::io(&tmp, "Failed to create", &e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut fonts = Vec::new();
+    let mut errors = Vec::new();
+    for mut font in files.into_values() {
+        let source = Path::new(&font.original_path);
+        let hash = match calculate_file_hash(source) {
+            Ok(hash) => hash,
+            Err(e) => {
+                errors.push(FontError::io(source, "Failed to read", &e));
+                continue;
+            }
+        };
+        font.hash = hash;
+        let written = File::open(source).and_then(|mut input| {
+            zip.start_file(font.archive_path.as_str(), options)
+                .map_err(io::Error::other)?;
+            io::copy(&mut input, &mut zip)
+        });
+        match written {
+            Ok(_) => fonts.push(font),
+            Err(e) => {
+                // A half-written entry would corrupt the archive
+                drop(zip);
+                let _ = fs::remove_file(&tmp);
+                return Err(FontError::io(source, "Failed to add to backup", &e));
+            }
+        }
+    }
+
+    let manifest = BackupManifest {
+        manifest_version: MANIFEST_VERSION,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        computer_name: std::env::var("COMPUTERNAME").ok(),
+        fonts,
+    };
+    let finished = serde_json::to_vec_pretty(&manifest)
+        .map_err(io::Error::other)
+        .and_then(|data| {
+            zip.start_file(MANIFEST_NAME, options).map_err(io::Error::other)?;
+            io::Write::write_all(&mut zip, &data)?;
+            zip.finish().map_err(io::Error::other)
+        })
+        .and_then(|_| fs::rename(&tmp, dest));
+    if let Err(e) = finished {
+        let _ = fs::remove_file(&tmp);
+        return Err(FontError::io(dest, "Failed to write backup", &e));
+    }
+
+    Ok(BackupResult {
+        path,
+        font_count: manifest.fonts.len(),
+        errors,
+    })
+}
+
+// Reinstalls a backup through the normal install pipeline under the original registry value
+// names, then merges the saved tags back in
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    path: String,
+    options: Option<InstallOptions>,
+    batch_id: Option<String>,
+) -> Result<RestoreResult, FontError> {
+    let archive_path = Path::new(&path);
+    let file = File::open(archive_path).map_err(|e| FontError::io(archive_path, "Failed to open", &e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| not_a_backup(archive_path, e))?;
+    let manifest: BackupManifest = {
+        let mut entry = archive
+            .by_name(MANIFEST_NAME)
+            .map_err(|e| not_a_backup(archive_path, e))?;
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| FontError::io(archive_path, "Failed to read backup manifest", &e))?;
+        serde_json::from_slice(&data).map_err(|e| not_a_backup(archive_path, e))?
+    };
+
+    let staging = app_data_dir(&app)?.join(format!(
+        "restore-{:x}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    ));
+    let result = restore_from(&app, &mut archive, &manifest, &staging, options, batch_id);
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn restore_from(
+    app: &AppHandle,
+    archive: &mut ZipArchive<File>,
+    manifest: &BackupManifest,
+    staging: &Path,
+    options: Option<InstallOptions>,
+    batch_id: Option<String>,
+) -> Result<RestoreResult, FontError> {
+    let mut errors = Vec::new();
+    // Extracted path -> the manifest entry it came from
+    let mut extracted: Vec<(PathBuf, &BackupFont)> = Vec::new();
+    for (i, font) in manifest.fonts.iter().enumerate() {
+        // Only the bare file name is trusted, so a crafted manifest can't write outside staging
+        let Some(file_name) = Path::new(&font.file_name).file_name() else {
+            continue;
+        };
+        let dir = staging.join(i.to_string());
+        let target = dir.join(file_name);
+        let written = fs::create_dir_all(&dir).and_then(|_| {
+            let mut entry = archive.by_name(&font.archive_path).map_err(io::Error::other)?;
+            let mut output = File::create(&target)?;
+            io::copy(&mut entry, &mut output)
+        });
+        if let Err(e) = written {
+            errors.push(FontError::io(
+                Path::new(&font.original_path),
+                "Failed to extract from backup",
+                &e,
+            ));
+            continue;
+        }
+        if calculate_file_hash(&target).ok().as_deref() != Some(font.hash.as_str()) {
+            errors.push(FontError::new(
+                FontErrorKind::InvalidFont,
+                Some(Path::new(&font.original_path)),
+                format!("{} in the backup is damaged", font.file_name),
+            ));
+            continue;
+        }
+        extracted.push((target, font));
+    }
+
+    let paths: Vec<String> = extracted.iter().map(|(p, _)| p.to_string_lossy().to_string()).collect();
+    let batch = begin_batch(app, batch_id);
+    let mut plan = build_plan(&paths, &options.unwrap_or_default(), Some(&batch.cancel));
+    let hive = plan.scope.hive();
+    for item in &mut plan.items {
+        // Keep Both installs under a new file name, so they keep the planner's value name too
+        if !matches!(item.action, PlannedAction::Install | PlannedAction::Overwrite | PlannedAction::Upgrade) {
+            continue;
+        }
+        let Some(destination) = item.destination.as_deref() else {
+            continue;
+        };
+        let original = extracted
+            .iter()
+            .find(|(p, _)| p.to_string_lossy() == item.source.as_str())
+            .and_then(|(_, font)| font.registry_value_names.first())
+            .filter(|name| is_font_registry_name(name));
+        let Some(name) = original else {
+            continue;
+        };
+        // Never take over a value that registers some other file
+        let free = match get_font_registry_value(hive, name) {
+            Ok(None) => true,
+            Ok(Some(data)) => same_path(&resolve_registry_path(&data), Path::new(destination)),
+            Err(_) => false,
+        };
+        if free {
+            item.registry_name = Some(name.clone());
+        }
+    }
+    let install = run_batch(app, &plan, &batch);
+
+    let mut tags = BTreeMap::new();
+    let mut tagged_fonts = 0;
+    for (_, font) in &extracted {
+        if !font.tags.is_empty() {
+            tagged_fonts += 1;
+            tags.extend(font.tags.clone());
+        }
+    }
+    if !tags.is_empty() {
+        // The fonts are back either way; a tag failure shouldn't hide the install result
+        if let Err(error) = merge_tags(app, tags) {
+            errors.push(error);
+        }
+    }
+
+    Ok(RestoreResult {
+        install,
+        tagged_fonts,
+        errors,
+    })
+}
+
+fn not_a_backup(path: &Path, error: impl std::fmt::Display) -> FontError {
+    FontError::new(
+        FontErrorKind::ParseFailed,
+        Some(path),
+        format!("{} is not a font backup: {}", path.display(), error),
+    )
+}
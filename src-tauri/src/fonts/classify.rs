@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use ttf_parser::{Face, PlatformId, Tag};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FontClass {
+    Serif,
+    Sans,
+    Slab,
+    Monospace,
+    Script,
+    Decorative,
+    Symbol,
+    // Neither OS/2 field says, and the face isn't monospaced or symbol-encoded
+    #[default]
+    Unknown,
+}
+
+pub fn classify_face(face: &Face) -> FontClass {
+    let os2 = face.raw_face().table(Tag::from_bytes(b"OS/2"));
+    // Windows symbol encoding: glyphs are mapped at U+F000 and up rather than by meaning
+    let symbol_cmap = face.tables().cmap.is_some_and(|cmap| {
+        cmap.subtables
+            .into_iter()
+            .any(|s| s.platform_id == PlatformId::Windows && s.encoding_id == 0)
+    });
+    classify(
+        // sFamilyClass high byte: the class, the low byte is its subclass
+        os2.and_then(|t| t.get(30)).copied().unwrap_or(0),
+        os2.and_then(|t| t.get(32..42)),
+        // post.isFixedPitch
+        face.is_monospaced(),
+        symbol_cmap,
+    )
+}
+
+// Most specific first: symbol and monospace cut across the design classes, then the
+// designer-declared OS/2 family class, then PANOSE
+fn classify(family_class: u8, panose: Option<&[u8]>, monospaced: bool, symbol_cmap: bool) -> FontClass {
+    // bFamilyType, bSerifStyle, bWeight, bProportion, ...
+    let panose_family = panose.map_or(0, |p| p[0]);
+    // Only meaningful for Latin text faces (family type 2)
+    let panose_text = |i: usize| panose.filter(|p| p[0] == 2).map_or(0, |p| p[i]);
+
+    if symbol_cmap || family_class == 12 || panose_family == 5 {
+        return FontClass::Symbol;
+    }
+    // PANOSE proportion "monospaced"
+    if monospaced || panose_text(3) == 9 {
+        return FontClass::Monospace;
+    }
+
+    match family_class {
+        1 | 2 | 3 | 7 => return FontClass::Serif,
+        4 | 5 => return FontClass::Slab,
+        8 => return FontClass::Sans,
+        9 => return FontClass::Decorative,
+        10 => return FontClass::Script,
+        _ => {}
+    }
+
+    match (panose_family, panose_text(1)) {
+        (3, _) => FontClass::Script,
+        (4, _) => FontClass::Decorative,
+        // Square cove, obtuse square cove and square serifs
+        (2, 4..=6) => FontClass::Slab,
+        (2, 2..=10) => FontClass::Serif,
+        // Normal, obtuse, perpendicular, flared and rounded sans
+        (2, 11..=15) => FontClass::Sans,
+        _ => FontClass::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PANOSE bytes for a Latin text face with the given serif style and proportion
+    fn text_panose(serif_style: u8, proportion: u8) -> [u8; 10] {
+        [2, serif_style, 5, proportion, 0, 0, 0, 0, 0, 0]
+    }
+
+    #[test]
+    fn symbol_wins_over_everything() {
+        assert_eq!(classify(8, None, true, true), FontClass::Symbol);
+        assert_eq!(classify(12, None, false, false), FontClass::Symbol);
+        assert_eq!(classify(0, Some(&[5, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false, false), FontClass::Symbol);
+    }
+
+    #[test]
+    fn monospace_wins_over_design_class() {
+        assert_eq!(classify(8, None, true, false), FontClass::Monospace);
+        assert_eq!(classify(1, Some(&text_panose(2, 9)), false, false), FontClass::Monospace);
+    }
+
+    #[test]
+    fn family_class_decides_before_panose() {
+        let sans = text_panose(11, 3);
+        for class in [1, 2, 3, 7] {
+            assert_eq!(classify(class, Some(&sans), false, false), FontClass::Serif);
+        }
+        assert_eq!(classify(4, Some(&sans), false, false), FontClass::Slab);
+        assert_eq!(classify(5, Some(&sans), false, false), FontClass::Slab);
+        assert_eq!(classify(8, Some(&text_panose(2, 3)), false, false), FontClass::Sans);
+        assert_eq!(classify(9, None, false, false), FontClass::Decorative);
+        assert_eq!(classify(10, None, false, false), FontClass::Script);
+    }
+
+    #[test]
+    fn panose_family_types() {
+        assert_eq!(classify(0, Some(&[3, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false, false), FontClass::Script);
+        assert_eq!(classify(0, Some(&[4, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false, false), FontClass::Decorative);
+    }
+
+    #[test]
+    fn panose_serif_styles() {
+        for style in [4, 5, 6] {
+            assert_eq!(classify(0, Some(&text_panose(style, 3)), false, false), FontClass::Slab);
+        }
+        for style in [2, 3, 7, 8, 9, 10] {
+            assert_eq!(classify(0, Some(&text_panose(style, 3)), false, false), FontClass::Serif);
+        }
+        for style in 11..=15 {
+            assert_eq!(classify(0, Some(&text_panose(style, 3)), false, false), FontClass::Sans);
+        }
+    }
+
+    #[test]
+    fn unknown_without_a_hint() {
+        assert_eq!(classify(0, None, false, false), FontClass::Unknown);
+        assert_eq!(classify(0, Some(&text_panose(0, 0)), false, false), FontClass::Unknown);
+        assert_eq!(classify(0, Some(&text_panose(1, 3)), false, false), FontClass::Unknown);
+    }
+}
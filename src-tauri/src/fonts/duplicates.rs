@@ -0,0 +1,264 @@
+use crate::fonts::enumerate::cached_variants;
+use crate::fonts::install::calculate_file_hash;
+use crate::fonts::{system_fonts_dir, user_fonts_dir, FontScope};
+use crate::win::registry::FontHive;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum FontLocation {
+    // Declared in load order: Windows loads machine fonts before per-user ones
+    System,
+    User,
+    // Registered from elsewhere, e.g. fonts activated from the app library
+    Other,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateKind {
+    Content,
+    PostscriptName,
+    FamilyStyle,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCopy {
+    pub path: String,
+    pub face_index: u32,
+    pub postscript_name: String,
+    pub family: String,
+    pub style: String,
+    pub weight: u16,
+    pub location: FontLocation,
+    pub hash: Option<String>,
+    // The hive whose Fonts key registers the file; None for files Windows only finds by scanning
+    pub registry_hive: Option<FontHive>,
+    // The copy Windows resolves the name to; the others are shadowed.
+    pub is_active: bool,
+    pub recommend_removal: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    pub key: String,
+    // All copies have the same file content.
+    pub identical: bool,
+    pub copies: Vec<DuplicateCopy>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub recommended_removals: Vec<String>,
+}
+
+struct FaceEntry {
+    path: PathBuf,
+    face_index: u32,
+    postscript_name: String,
+    family: String,
+    style: String,
+    weight: u16,
+    location: FontLocation,
+    registry_hive: Option<FontHive>,
+}
+
+#[tauri::command]
+pub async fn find_duplicate_fonts(app: AppHandle) -> DuplicateReport {
+    let system_dir = system_fonts_dir();
+    let user_dir = user_fonts_dir();
+
+    let mut faces = Vec::new();
+    for variant in cached_variants(&app, false) {
+        let path = PathBuf::from(&variant.path);
+        let location = if path.starts_with(&system_dir) {
+            FontLocation::System
+        } else if path.starts_with(&user_dir) {
+            FontLocation::User
+        } else {
+            FontLocation::Other
+        };
+
+        // The scope comes from the same registration as the value name, so it tells the hive
+        let registry_hive = match (&variant.registry_value_name, variant.scope) {
+            (None, _) => None,
+            (Some(_), FontScope::PerUser) => Some(FontHive::CurrentUser),
+            (Some(_), _) => Some(FontHive::LocalMachine),
+        };
+
+        faces.push(FaceEntry {
+            face_index: variant.face_index,
+            postscript_name: variant.postscript_name.unwrap_or_default(),
+            family: variant.family,
+            style: variant.style,
+            weight: variant.weight,
+            location,
+            registry_hive,
+            path,
+        });
+    }
+
+    // Deterministic "first wins" order inside a location
+    faces.sort_by(|a, b| (a.location, &a.path, a.face_index).cmp(&(b.location, &b.path, b.face_index)));
+
+    let hashes = hash_size_collisions(&faces);
+
+    let mut groups = Vec::new();
+
+    // 1. Same file content under different paths
+    let mut by_hash: BTreeMap<&str, Vec<&FaceEntry>> = BTreeMap::new();
+    let mut seen_paths = HashSet::new();
+    for face in &faces {
+        if let Some(hash) = hashes.get(&face.path) {
+            if seen_paths.insert(&face.path) {
+                by_hash.entry(hash.as_str()).or_default().push(face);
+            }
+        }
+    }
+    for (hash, members) in by_hash {
+        if members.len() > 1 {
+            groups.push(build_group(DuplicateKind::Content, hash.to_string(), &members, &hashes));
+        }
+    }
+
+    // 2. Different files claiming the same PostScript name
+    let mut by_ps_name: BTreeMap<String, Vec<&FaceEntry>> = BTreeMap::new();
+    for face in &faces {
+        if !face.postscript_name.is_empty() {
+            by_ps_name.entry(face.postscript_name.clone()).or_default().push(face);
+        }
+    }
+    for (name, members) in &by_ps_name {
+        if distinct_paths(members) > 1 {
+            groups.push(build_group(DuplicateKind::PostscriptName, name.clone(), members, &hashes));
+        }
+    }
+
+    // 3. Same family + style under different PostScript names (not already covered above)
+    let mut by_family_style: BTreeMap<String, Vec<&FaceEntry>> = BTreeMap::new();
+    for face in &faces {
+        let key = format!("{} {} {}", face.family, face.weight, face.style);
+        by_family_style.entry(key).or_default().push(face);
+    }
+    for (key, members) in by_family_style {
+        let ps_names: HashSet<&str> = members.iter().map(|f| f.postscript_name.as_str()).collect();
+        if distinct_paths(&members) > 1 && ps_names.len() > 1 {
+            groups.push(build_group(DuplicateKind::FamilyStyle, key, &members, &hashes));
+        }
+    }
+
+    let mut recommended_removals: Vec<String> = groups
+        .iter()
+        .flat_map(|g| g.copies.iter())
+        .filter(|c| c.recommend_removal)
+        .map(|c| c.path.clone())
+        .collect();
+    recommended_removals.sort();
+    recommended_removals.dedup();
+
+    // A file must never be both kept as the active copy somewhere and recommended for removal
+    let active_paths: HashSet<String> = groups
+        .iter()
+        .flat_map(|g| g.copies.iter())
+        .filter(|c| c.is_active)
+        .map(|c| c.path.clone())
+        .collect();
+    recommended_removals.retain(|p| !active_paths.contains(p));
+    for copy in groups.iter_mut().flat_map(|g| g.copies.iter_mut()) {
+        if active_paths.contains(&copy.path) {
+            copy.recommend_removal = false;
+        }
+    }
+
+    DuplicateReport {
+        groups,
+        recommended_removals,
+    }
+}
+
+// Only files that share their size with another file can be byte-identical, so only those get hashed
+fn hash_size_collisions(faces: &[FaceEntry]) -> HashMap<PathBuf, String> {
+    let mut by_size: HashMap<u64, HashSet<&Path>> = HashMap::new();
+    for face in faces {
+        if let Ok(meta) = fs::metadata(&face.path) {
+            by_size.entry(meta.len()).or_default().insert(face.path.as_path());
+        }
+    }
+
+    let mut hashes = HashMap::new();
+    for paths in by_size.values().filter(|p| p.len() > 1) {
+        for path in paths {
+            if let Ok(hash) = calculate_file_hash(path) {
+                hashes.insert(path.to_path_buf(), hash);
+            }
+        }
+    }
+    hashes
+}
+
+fn distinct_paths(members: &[&FaceEntry]) -> usize {
+    members.iter().map(|f| &f.path).collect::<HashSet<_>>().len()
+}
+
+fn build_group(
+    kind: DuplicateKind,
+    key: String,
+    members: &[&FaceEntry],
+    hashes: &HashMap<PathBuf, String>,
+) -> DuplicateGroup {
+    // Windows loads the registered fonts, HKLM before HKCU, and resolves a name to the first it
+    // loaded; unregistered copies only count when nothing registered is in the group.
+    // Members are in folder order, which breaks ties within a hive.
+    let hive_rank = |hive: Option<FontHive>| match hive {
+        Some(FontHive::LocalMachine) => 0,
+        Some(FontHive::CurrentUser) => 1,
+        None => 2,
+    };
+    let active_path = &members
+        .iter()
+        .enumerate()
+        .min_by_key(|(i, m)| (hive_rank(m.registry_hive), *i))
+        .map(|(_, m)| m)
+        .unwrap_or(&members[0])
+        .path;
+
+    let first_hash = hashes.get(active_path);
+    let identical = first_hash.is_some() && members.iter().all(|m| hashes.get(&m.path) == first_hash);
+
+    let copies = members
+        .iter()
+        .map(|m| {
+            let is_active = &m.path == active_path;
+            DuplicateCopy {
+                path: m.path.to_string_lossy().to_string(),
+                face_index: m.face_index,
+                postscript_name: m.postscript_name.clone(),
+                family: m.family.clone(),
+                style: m.style.clone(),
+                weight: m.weight,
+                location: m.location,
+                hash: hashes.get(&m.path).cloned(),
+                registry_hive: m.registry_hive,
+                is_active,
+                // System copies can't be removed from here; shadowed user copies are dead weight
+                recommend_removal: !is_active && m.location != FontLocation::System,
+            }
+        })
+        .collect();
+
+    DuplicateGroup {
+        kind,
+        key,
+        identical,
+        copies,
+    }
+}
@@ -0,0 +1,257 @@
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::install::{
+    batch_failed, execute_plan, InstallDecision, InstallFileResult, InstallOptions, InstallResult, InstallScope,
+    ProgressSink, same_path,
+};
+use crate::fonts::plan::{build_plan, InstallPlan, PlannedAction};
+use crate::fonts::system_fonts_dir;
+use crate::win::run_elevated_and_wait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// `font-manager.exe --elevated-install <request.json> <result.json>`
+const ELEVATED_INSTALL_ARG: &str = "--elevated-install";
+
+// ERROR_CANCELLED, returned when the user says no to the UAC prompt
+const ERROR_CANCELLED: i32 = 1223;
+
+// What the helper is asked to install. The request file sits in a user-writable temp directory,
+// so it carries no destinations or registry names: the helper plans the files itself.
+#[derive(Serialize, Deserialize)]
+struct ElevatedRequest {
+    paths: Vec<String>,
+    options: InstallOptions,
+}
+
+// Runs a machine-wide plan in an elevated copy of this executable and collects its result
+pub fn install_elevated(plan: &InstallPlan) -> InstallResult {
+    // Files the user chose to skip are left out; everything else is planned again by the helper
+    let sent: Vec<usize> = (0..plan.items.len())
+        .filter(|&i| plan.items[i].action != PlannedAction::Skip)
+        .collect();
+    let request = ElevatedRequest {
+        paths: sent.iter().map(|&i| plan.items[i].source.clone()).collect(),
+        options: InstallOptions {
+            conflict_policy: plan.conflict_policy,
+            atomic: plan.atomic,
+            scope: plan.scope,
+        },
+    };
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let work_dir = std::env::temp_dir().join(format!("font-manager-elevated-{}-{}", std::process::id(), nanos));
+    let result = run_helper(&request, &work_dir);
+    let _ = fs::remove_dir_all(&work_dir);
+    match result {
+        Ok(result) => with_skipped(plan, &sent, result),
+        Err(error) => batch_failed(plan, error),
+    }
+}
+
+// Puts the items that weren't sent back in plan order
+fn with_skipped(plan: &InstallPlan, sent: &[usize], mut result: InstallResult) -> InstallResult {
+    if result.files.len() != sent.len() {
+        // The helper failed the batch as a whole
+        return result;
+    }
+    let mut installed = result.files.into_iter();
+    result.files = plan
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if sent.contains(&i) {
+                return installed.next().unwrap();
+            }
+            InstallFileResult {
+                path: item.source.clone(),
+                decision: InstallDecision::Skipped,
+                destination: None,
+                message: item.message.clone(),
+                error: None,
+            }
+        })
+        .collect();
+    result.skipped_count += plan.items.len() - sent.len();
+    result
+}
+
+fn run_helper(request: &ElevatedRequest, work_dir: &Path) -> Result<InstallResult, FontError> {
+    fs::create_dir_all(work_dir).map_err(|e| FontError::io(work_dir, "Failed to create directory", &e))?;
+    let request_path = work_dir.join("request.json");
+    let result_path = work_dir.join("result.json");
+
+    let request_json = serde_json::to_vec(request).map_err(|e| {
+        FontError::new(FontErrorKind::Io, Some(&request_path), format!("Failed to serialize install request: {}", e))
+    })?;
+    fs::write(&request_path, request_json).map_err(|e| FontError::io(&request_path, "Failed to write", &e))?;
+
+    let exe = std::env::current_exe().map_err(|e| FontError::io(Path::new("font-manager.exe"), "Failed to locate", &e))?;
+    let parameters = format!(
+        "{} \"{}\" \"{}\"",
+        ELEVATED_INSTALL_ARG,
+        request_path.display(),
+        result_path.display()
+    );
+    match run_elevated_and_wait(&exe, &parameters) {
+        Ok(_) => {}
+        Err(e) if e.raw_os_error() == Some(ERROR_CANCELLED) => {
+            return Err(FontError {
+                os_code: Some(ERROR_CANCELLED),
+                ..FontError::new(
+                    FontErrorKind::AccessDenied,
+                    None,
+                    "Administrator permission is needed to install fonts for all users",
+                )
+            });
+        }
+        Err(e) => return Err(FontError::io(&exe, "Failed to start elevated installer", &e)),
+    }
+
+    // The helper writes a result even when the install itself failed; no file means it crashed
+    let data = fs::read(&result_path).map_err(|e| FontError::io(&result_path, "Elevated installer left no result at", &e))?;
+    serde_json::from_slice(&data).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(&result_path),
+            format!("Failed to parse elevated install result: {}", e),
+        )
+    })
+}
+
+// Entry point of the elevated process. Returns None when not started as the helper.
+pub fn run_elevated_helper() -> Option<i32> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) != Some(ELEVATED_INSTALL_ARG) || args.len() != 4 {
+        return None;
+    }
+    let request_path = PathBuf::from(&args[2]);
+    let result_path = PathBuf::from(&args[3]);
+
+    let request: ElevatedRequest = match fs::read(&request_path).ok().and_then(|d| serde_json::from_slice(&d).ok()) {
+        Some(request) => request,
+        None => return Some(1),
+    };
+
+    let result = install_request(&request);
+
+    match serde_json::to_vec(&result).map(|json| fs::write(&result_path, json)) {
+        Ok(Ok(())) => Some(0),
+        _ => Some(1),
+    }
+}
+
+fn install_request(request: &ElevatedRequest) -> InstallResult {
+    let failed = |error: FontError| InstallResult {
+        success_count: 0,
+        failed_count: request.paths.len(),
+        skipped_count: 0,
+        errors: vec![error],
+        warnings: Vec::new(),
+        files: Vec::new(),
+        rollback: None,
+        cancelled: false,
+    };
+    if request.options.scope != InstallScope::AllUsers {
+        return failed(FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            None,
+            "Elevated installs only write to the system fonts directory",
+        ));
+    }
+
+    // The sources are user-writable and could be swapped after they were checked, so the helper
+    // copies them somewhere only administrators can write and plans and installs those copies.
+    // The atomic journal keeps its backups there as well instead of in the user's temp directory.
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let staging_dir = system_temp_dir().join(format!("font-manager-staging-{}-{}", std::process::id(), nanos));
+
+    let result = stage_sources(&request.paths, &staging_dir).map(|staged| {
+        let plan = build_plan(&staged, &request.options, None);
+        let result = match check_machine_plan(&plan) {
+            Ok(()) => {
+                let progress = ProgressSink::new(None, None, plan.items.len(), Arc::new(AtomicBool::new(false)));
+                execute_plan(&plan, &progress, &staging_dir)
+            }
+            Err(error) => batch_failed(&plan, error),
+        };
+        unstage(result, &staged, &request.paths)
+    });
+    let _ = fs::remove_dir_all(&staging_dir);
+    result.unwrap_or_else(failed)
+}
+
+// %SystemRoot%\Temp; folders an administrator creates there can't be changed by other users
+fn system_temp_dir() -> PathBuf {
+    let fonts_dir = system_fonts_dir();
+    fonts_dir.parent().unwrap_or(&fonts_dir).join("Temp")
+}
+
+// Copies each source to <staging>/<index>/<file name>, so planning sees the original file names
+fn stage_sources(paths: &[String], staging_dir: &Path) -> Result<Vec<String>, FontError> {
+    let mut staged = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        let source = Path::new(path);
+        let file_name = source.file_name().ok_or_else(|| {
+            FontError::new(FontErrorKind::NotFound, Some(source), format!("{} is not a file", path))
+        })?;
+        let dir = staging_dir.join(i.to_string());
+        fs::create_dir_all(&dir).map_err(|e| FontError::io(&dir, "Failed to create directory", &e))?;
+        let copy = dir.join(file_name);
+        fs::copy(source, &copy).map_err(|e| FontError::io(source, "Failed to copy", &e))?;
+        staged.push(copy.to_string_lossy().to_string());
+    }
+    Ok(staged)
+}
+
+// Reports the results against the files the user picked rather than the staged copies
+fn unstage(mut result: InstallResult, staged: &[String], originals: &[String]) -> InstallResult {
+    let original = |path: &mut String| {
+        if let Some(i) = staged.iter().position(|s| s == path) {
+            *path = originals[i].clone();
+        }
+    };
+    for file in &mut result.files {
+        original(&mut file.path);
+        if let Some(path) = file.error.as_mut().and_then(|e| e.path.as_mut()) {
+            original(path);
+        }
+    }
+    for path in result.errors.iter_mut().filter_map(|e| e.path.as_mut()) {
+        original(path);
+    }
+    for warning in &mut result.warnings {
+        original(&mut warning.path);
+    }
+    result
+}
+
+// Last line of defence in the elevated process: machine-wide plans may only write straight
+// into %SystemRoot%\Fonts. Also run when the app itself is already elevated.
+pub(crate) fn check_machine_plan(plan: &InstallPlan) -> Result<(), FontError> {
+    let fonts_dir = system_fonts_dir();
+    if plan.scope != InstallScope::AllUsers || !same_path(Path::new(&plan.fonts_dir), &fonts_dir) {
+        return Err(FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            Some(Path::new(&plan.fonts_dir)),
+            "Elevated installs only write to the system fonts directory",
+        ));
+    }
+    for item in &plan.items {
+        if let Some(dest) = &item.destination {
+            let dest = Path::new(dest);
+            if dest.file_name().is_none() || !dest.parent().is_some_and(|parent| same_path(parent, &fonts_dir)) {
+                return Err(FontError::new(
+                    FontErrorKind::OutsideAllowedDir,
+                    Some(dest),
+                    format!("{} is outside the system fonts directory", dest.display()),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,201 +1,377 @@
-use crate::fonts::{FontFamily, FontVariant, ListFontsResult};
-use fontdb::{Database, Source};
+use crate::fonts::classify::{classify_face, FontClass};
+use crate::fonts::identity::{face_identity, name_version_key};
+use crate::fonts::library::list_library_variants;
+use crate::fonts::licence::{read_licence, LicenceInfo};
+use crate::fonts::metadata::{read_face_metadata, FaceMetadata};
+use crate::fonts::organize::{filter_variants, FontFilter};
+use crate::fonts::similar::Profile;
+use crate::fonts::{resolve_registry_path, system_fonts_dir, user_fonts_dir, FontFamily, FontScope, FontVariant, ListFontsResult};
+use crate::win::is_owned_by_trusted_installer;
+use crate::win::registry::{list_font_registry_values, FontHive};
+use fontdb::{Database, FaceInfo, Source};
 use sha1::{Digest, Sha1};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
 use ttf_parser::Face;
 
-#[tauri::command]
-pub fn list_fonts() -> ListFontsResult {
-    let mut db = Database::new();
-    db.load_system_fonts();
+// Described variants kept between searches, since describing them reads every font file.
+// Anything that installs, removes or (de)activates fonts calls invalidate_variants.
+#[derive(Default)]
+pub struct VariantCache {
+    inner: Mutex<CachedVariants>,
+    // Similarity profiles by identity. They follow from the face's content, so invalidations keep them.
+    pub(crate) profiles: Mutex<HashMap<String, Profile>>,
+}
 
-    let mut families_map: HashMap<String, Vec<FontVariant>> = HashMap::new();
+#[derive(Default)]
+struct CachedVariants {
+    // Bumped on every invalidation, so a listing that raced with one isn't stored
+    generation: u64,
+    // By include_library
+    variants: HashMap<bool, Vec<FontVariant>>,
+}
 
-    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
-    let system_fonts_dir = Path::new(&system_root).join("Fonts");
-
-    // Helper to map weight to string
-    fn weight_to_name(weight: u16) -> &'static str {
-        match weight {
-            100..=199 => "Thin",
-            200..=299 => "ExtraLight",
-            300..=349 => "Light",
-            350..=399 => "DemiLight",
-            400..=499 => "Regular",
-            500..=599 => "Medium",
-            600..=699 => "SemiBold",
-            700..=799 => "Bold",
-            800..=899 => "ExtraBold",
-            900..=999 => "Black",
-            _ => "Regular",
-        }
+// Installed fonts, plus the app library's fonts when asked for, optionally narrowed by tag,
+// collection or favourites
+#[tauri::command]
+pub fn list_fonts(app: AppHandle, include_library: Option<bool>, filter: Option<FontFilter>) -> ListFontsResult {
+    // Always a fresh listing; it also refreshes what searches see
+    invalidate_variants(&app);
+    let mut variants = cached_variants(&app, include_library.unwrap_or(false));
+    if let Some(filter) = filter {
+        variants = filter_variants(&app, &filter, variants);
     }
+    group_families(variants)
+}
 
-    fn is_regular_weight(weight: u16) -> bool {
-        weight >= 400 && weight <= 499
+// Installed variants, plus library-only ones when asked for
+pub(crate) fn collect_variants(app: &AppHandle, include_library: bool) -> Vec<FontVariant> {
+    let mut variants = installed_variants();
+    if include_library {
+        // A library font that is also installed shows up once, as the installed variant
+        let library = list_library_variants(app, &mut variants).unwrap_or_default();
+        variants.extend(library);
     }
+    variants
+}
 
-    for face_info in db.faces() {
-        // Fix for fonts where OS/2 weight is 400 (Regular) but PostScript name indicates otherwise (e.g. MiSans Thin)
-        let mut weight = face_info.weight.0;
-        if weight == 400 {
-             let ps_name_lower = face_info.post_script_name.to_lowercase();
-             if ps_name_lower.contains("thin") { weight = 100; }
-             else if ps_name_lower.contains("extralight") { weight = 200; }
-             else if ps_name_lower.contains("demilight") { weight = 350; }
-             else if ps_name_lower.contains("light") { weight = 300; }
-             else if ps_name_lower.contains("medium") { weight = 500; }
-             else if ps_name_lower.contains("semibold") { weight = 600; }
-             else if ps_name_lower.contains("extrabold") { weight = 800; }
-             else if ps_name_lower.contains("bold") { weight = 700; }
-             else if ps_name_lower.contains("black") { weight = 900; }
-             else if ps_name_lower.contains("heavy") { weight = 900; }
+// collect_variants, served from the cache when nothing changed since the last call
+pub(crate) fn cached_variants(app: &AppHandle, include_library: bool) -> Vec<FontVariant> {
+    let cache = app.state::<VariantCache>();
+    let generation = {
+        let cached = cache.inner.lock().unwrap();
+        if let Some(variants) = cached.variants.get(&include_library) {
+            return variants.clone();
         }
+        cached.generation
+    };
 
-        let family = face_info
-            .families
-            .first()
-            .map(|f| f.0.clone())
-            .unwrap_or("Unknown".to_string());
-
-        // Generate a stable ID
-        let path_str = match &face_info.source {
-            Source::File(path) => path.to_string_lossy().to_string(),
-            Source::Binary(_) => "memory".to_string(),
-            Source::SharedFile(path, _) => path.to_string_lossy().to_string(),
-        };
+    let variants = collect_variants(app, include_library);
+    let mut cached = cache.inner.lock().unwrap();
+    if cached.generation == generation {
+        cached.variants.insert(include_library, variants.clone());
+    }
+    variants
+}
 
-        let mut hasher = Sha1::new();
-        hasher.update(path_str.as_bytes());
-        hasher.update(face_info.post_script_name.as_bytes());
-        let id = hex::encode(hasher.finalize());
+pub(crate) fn invalidate_variants(app: &AppHandle) {
+    let cache = app.state::<VariantCache>();
+    let mut cached = cache.inner.lock().unwrap();
+    cached.generation += 1;
+    cached.variants.clear();
+}
 
-        let style_str = match face_info.style {
-            fontdb::Style::Normal => "normal",
-            fontdb::Style::Italic => "italic",
-            fontdb::Style::Oblique => "oblique",
-        };
+pub fn installed_fonts() -> ListFontsResult {
+    group_families(installed_variants())
+}
+
+pub(crate) fn installed_variants() -> Vec<FontVariant> {
+    let registrations = font_registrations();
+    let db = installed_font_database(&registrations);
+
+    let system_fonts_dir = system_fonts_dir();
 
-        let is_system_core = if let Source::File(path) = &face_info.source {
+    // Ownership lookups per file, collections share one
+    let mut shipped_with_windows: HashMap<String, bool> = HashMap::new();
+
+    let mut variants = Vec::new();
+    for face_info in db.faces() {
+        let mut variant = describe_face(face_info);
+        let path_str = variant.path.clone();
+
+        variant.is_system_core = if let Source::File(path) = &face_info.source {
             path.starts_with(&system_fonts_dir)
         } else {
             false
         };
 
-        // Try to get the full name using ttf-parser
-        let mut full_name = None;
-        if let Source::File(path) = &face_info.source {
-            if let Ok(file_data) = std::fs::read(path) {
-                // Parse the face at the specific index
-                if let Ok(face) = Face::parse(&file_data, face_info.index) {
-                    // Strategy:
-                    // 1. Try to find Preferred Family (ID 16) + Preferred Subfamily (ID 17)
-                    // 2. If not found, try Family (ID 1) + Subfamily (ID 2)
-                    // 3. Fallback to Full Name (ID 4) if constructed name seems bad or missing parts
-                    // We prioritize Chinese (2052) then English (1033) for each ID.
-
-                    fn get_name(face: &Face, name_id: u16) -> Option<String> {
-                        let mut best_name = None;
-                        let mut best_score = 0; // 0: none, 1: any, 2: en, 3: cn
-                        for name in face.names() {
-                            if name.name_id == name_id && name.is_unicode() {
-                                let score = if name.language_id == 2052 { 3 } else if name.language_id == 1033 { 2 } else { 1 };
-                                if score > best_score {
-                                    if let Some(name_str) = name.to_string() {
-                                        best_name = Some(name_str);
-                                        best_score = score;
-                                    }
+        let registration = registrations.get(&path_str.to_lowercase());
+        let in_system_dir = Path::new(&path_str).starts_with(&system_fonts_dir);
+        let shipped = in_system_dir
+            && *shipped_with_windows
+                .entry(path_str.clone())
+                .or_insert_with(|| is_owned_by_trusted_installer(Path::new(&path_str)));
+        variant.scope = match registration.map(|r| r.hive) {
+            _ if shipped => FontScope::SystemCore,
+            Some(FontHive::LocalMachine) => FontScope::MachineInstalled,
+            Some(FontHive::CurrentUser) => FontScope::PerUser,
+            None => FontScope::Unregistered,
+        };
+        variant.registry_value_name = registration.map(|r| r.value_name.clone());
+
+        variants.push(variant);
+    }
+    variants
+}
+
+pub(crate) struct Registration {
+    pub hive: FontHive,
+    pub value_name: String,
+    pub path: PathBuf,
+}
+
+// Lowercased file path -> the value registering it; HKLM wins when both hives list a file
+pub(crate) fn font_registrations() -> HashMap<String, Registration> {
+    let mut registrations = HashMap::new();
+    for hive in [FontHive::LocalMachine, FontHive::CurrentUser] {
+        for (value_name, data) in list_font_registry_values(hive).unwrap_or_default() {
+            let path = resolve_registry_path(&data);
+            registrations
+                .entry(path.to_string_lossy().to_lowercase())
+                .or_insert(Registration { hive, value_name, path });
+        }
+    }
+    registrations
+}
+
+// The fonts Windows loads: fontdb scans the two fonts folders, and files registered from
+// anywhere else, e.g. fonts activated from the app library, are loaded one by one
+pub(crate) fn installed_font_database(registrations: &HashMap<String, Registration>) -> Database {
+    let mut db = Database::new();
+    db.load_system_fonts();
+    let dirs = [system_fonts_dir(), user_fonts_dir()];
+    for path in outside_dirs(registrations.values().map(|r| r.path.as_path()), &dirs) {
+        let _ = db.load_font_file(path);
+    }
+    db
+}
+
+// The paths that aren't inside any of the folders, compared case-insensitively, in sorted order
+fn outside_dirs<'a>(paths: impl IntoIterator<Item = &'a Path>, dirs: &[PathBuf]) -> Vec<&'a Path> {
+    let lower = |p: &Path| PathBuf::from(p.to_string_lossy().to_lowercase());
+    let dirs: Vec<PathBuf> = dirs.iter().map(|d| lower(d)).collect();
+    let mut outside: Vec<&Path> = paths
+        .into_iter()
+        .filter(|path| !dirs.iter().any(|dir| lower(path).starts_with(dir)))
+        .collect();
+    outside.sort();
+    outside
+}
+
+// Helper to map weight to string
+fn weight_to_name(weight: u16) -> &'static str {
+    match weight {
+        100..=199 => "Thin",
+        200..=299 => "ExtraLight",
+        300..=349 => "Light",
+        350..=399 => "DemiLight",
+        400..=499 => "Regular",
+        500..=599 => "Medium",
+        600..=699 => "SemiBold",
+        700..=799 => "Bold",
+        800..=899 => "ExtraBold",
+        900..=999 => "Black",
+        _ => "Regular",
+    }
+}
+
+fn is_regular_weight(weight: u16) -> bool {
+    weight >= 400 && weight <= 499
+}
+
+// Display names and weight for one face, with the install-related fields left at their defaults
+pub(crate) fn describe_face(face_info: &FaceInfo) -> FontVariant {
+    // Fix for fonts where OS/2 weight is 400 (Regular) but PostScript name indicates otherwise (e.g. MiSans Thin)
+    let mut weight = face_info.weight.0;
+    if weight == 400 {
+         let ps_name_lower = face_info.post_script_name.to_lowercase();
+         if ps_name_lower.contains("thin") { weight = 100; }
+         else if ps_name_lower.contains("extralight") { weight = 200; }
+         else if ps_name_lower.contains("demilight") { weight = 350; }
+         else if ps_name_lower.contains("light") { weight = 300; }
+         else if ps_name_lower.contains("medium") { weight = 500; }
+         else if ps_name_lower.contains("semibold") { weight = 600; }
+         else if ps_name_lower.contains("extrabold") { weight = 800; }
+         else if ps_name_lower.contains("bold") { weight = 700; }
+         else if ps_name_lower.contains("black") { weight = 900; }
+         else if ps_name_lower.contains("heavy") { weight = 900; }
+    }
+
+    let family = face_info
+        .families
+        .first()
+        .map(|f| f.0.clone())
+        .unwrap_or("Unknown".to_string());
+
+    // Generate a stable ID
+    let path_str = match &face_info.source {
+        Source::File(path) => path.to_string_lossy().to_string(),
+        Source::Binary(_) => "memory".to_string(),
+        Source::SharedFile(path, _) => path.to_string_lossy().to_string(),
+    };
+
+    let mut hasher = Sha1::new();
+    hasher.update(path_str.as_bytes());
+    hasher.update(face_info.post_script_name.as_bytes());
+    let id = hex::encode(hasher.finalize());
+
+    let style_str = match face_info.style {
+        fontdb::Style::Normal => "normal",
+        fontdb::Style::Italic => "italic",
+        fontdb::Style::Oblique => "oblique",
+    };
+
+    // Try to get the full name using ttf-parser
+    let mut full_name = None;
+    let mut identity = None;
+    let mut name_version = None;
+    let mut metadata = FaceMetadata::default();
+    let mut classification = FontClass::Unknown;
+    let mut licence = LicenceInfo::default();
+    if let Source::File(path) = &face_info.source {
+        if let Ok(file_data) = std::fs::read(path) {
+            // Parse the face at the specific index
+            if let Ok(face) = Face::parse(&file_data, face_info.index) {
+                identity = Some(face_identity(&face));
+                name_version = name_version_key(&face);
+                metadata = read_face_metadata(&face);
+                classification = classify_face(&face);
+                licence = read_licence(&face);
+
+                // Strategy:
+                // 1. Try to find Preferred Family (ID 16) + Preferred Subfamily (ID 17)
+                // 2. If not found, try Family (ID 1) + Subfamily (ID 2)
+                // 3. Fallback to Full Name (ID 4) if constructed name seems bad or missing parts
+                // We prioritize Chinese (2052) then English (1033) for each ID.
+
+                fn get_name(face: &Face, name_id: u16) -> Option<String> {
+                    let mut best_name = None;
+                    let mut best_score = 0; // 0: none, 1: any, 2: en, 3: cn
+                    for name in face.names() {
+                        if name.name_id == name_id && name.is_unicode() {
+                            let score = if name.language_id == 2052 { 3 } else if name.language_id == 1033 { 2 } else { 1 };
+                            if score > best_score {
+                                if let Some(name_str) = name.to_string() {
+                                    best_name = Some(name_str);
+                                    best_score = score;
                                 }
                             }
                         }
-                        best_name
                     }
+                    best_name
+                }
 
-                    let family_name = get_name(&face, 16).or_else(|| get_name(&face, 1));
-                    let subfamily_name = get_name(&face, 17).or_else(|| get_name(&face, 2));
-                    
-                    if let (Some(f), Some(s)) = (family_name, subfamily_name) {
-                         let s_lower = s.to_lowercase();
-                         // If subfamily is just "Regular" or "Normal", and weight is NOT regular, 
-                         // we suspect this is a compatibility legacy name.
-                         // We try to use the weight name instead.
-                         if (s_lower == "regular" || s_lower == "normal") && !is_regular_weight(weight) {
-                              let weight_name = weight_to_name(weight);
-                              full_name = Some(format!("{} {}", f, weight_name));
+                let family_name = get_name(&face, 16).or_else(|| get_name(&face, 1));
+                let subfamily_name = get_name(&face, 17).or_else(|| get_name(&face, 2));
+                
+                if let (Some(f), Some(s)) = (family_name, subfamily_name) {
+                     let s_lower = s.to_lowercase();
+                     // If subfamily is just "Regular" or "Normal", and weight is NOT regular, 
+                     // we suspect this is a compatibility legacy name.
+                     // We try to use the weight name instead.
+                     if (s_lower == "regular" || s_lower == "normal") && !is_regular_weight(weight) {
+                          let weight_name = weight_to_name(weight);
+                          full_name = Some(format!("{} {}", f, weight_name));
+                     } else {
+                         // Clean up: avoid "MiSans MiSans Bold"
+                         if s_lower.contains(&f.to_lowercase()) {
+                             full_name = Some(s);
                          } else {
-                             // Clean up: avoid "MiSans MiSans Bold"
-                             if s_lower.contains(&f.to_lowercase()) {
-                                 full_name = Some(s);
-                             } else {
-                                 full_name = Some(format!("{} {}", f, s));
-                             }
+                             full_name = Some(format!("{} {}", f, s));
                          }
-                    } else {
-                         // Fallback to ID 4
-                         full_name = get_name(&face, 4);
-                    }
+                     }
+                } else {
+                     // Fallback to ID 4
+                     full_name = get_name(&face, 4);
                 }
             }
         }
-        
-        // Fallback: If full_name is still None, use PostScript Name
-        if full_name.is_none() {
-             full_name = Some(face_info.post_script_name.clone());
-        }
+    }
+    
+    // Fallback: If full_name is still None, use PostScript Name
+    if full_name.is_none() {
+         full_name = Some(face_info.post_script_name.clone());
+    }
 
-        // ---------------------------------------------------------------------
-        // CRITICAL FIX 2: Even more Aggressive Name Correction
-        // ---------------------------------------------------------------------
-        
-        if let Some(ref name) = full_name {
-            // Check if weight is NOT regular (e.g. 200, 700)
-            if !is_regular_weight(weight) {
-                 let weight_name = weight_to_name(weight);
-                 let name_lower = name.to_lowercase();
-                 
-                 let mut new_name = name.clone();
-                 let mut changed = false;
-
-                 // Case insensitive replacement
-                 // We need to handle case insensitivity properly for replacement
-                 
-                 // Find "Normal" or "Regular" (case insensitive) and replace
-                 let target_words = ["Normal", "Regular", "normal", "regular"];
-                 for target in target_words {
-                     if new_name.contains(target) {
-                          new_name = new_name.replace(target, weight_name);
-                          changed = true;
-                     }
-                 }
-                 
-                 // If no replacement happened, maybe the name doesn't have the weight at all?
-                 // e.g. "MiSans" (missing weight) but weight is 200
-                 if !changed {
-                      let weight_lower = weight_name.to_lowercase();
-                      if !name_lower.contains(&weight_lower) {
-                          // Append the weight
-                          new_name = format!("{} {}", name, weight_name);
-                      }
+    // ---------------------------------------------------------------------
+    // CRITICAL FIX 2: Even more Aggressive Name Correction
+    // ---------------------------------------------------------------------
+    
+    if let Some(ref name) = full_name {
+        // Check if weight is NOT regular (e.g. 200, 700)
+        if !is_regular_weight(weight) {
+             let weight_name = weight_to_name(weight);
+             let name_lower = name.to_lowercase();
+             
+             let mut new_name = name.clone();
+             let mut changed = false;
+
+             // Case insensitive replacement
+             // We need to handle case insensitivity properly for replacement
+             
+             // Find "Normal" or "Regular" (case insensitive) and replace
+             let target_words = ["Normal", "Regular", "normal", "regular"];
+             for target in target_words {
+                 if new_name.contains(target) {
+                      new_name = new_name.replace(target, weight_name);
+                      changed = true;
                  }
-                 
-                 full_name = Some(new_name);
-            }
+             }
+             
+             // If no replacement happened, maybe the name doesn't have the weight at all?
+             // e.g. "MiSans" (missing weight) but weight is 200
+             if !changed {
+                  let weight_lower = weight_name.to_lowercase();
+                  if !name_lower.contains(&weight_lower) {
+                      // Append the weight
+                      new_name = format!("{} {}", name, weight_name);
+                  }
+             }
+             
+             full_name = Some(new_name);
         }
+    }
 
-        let variant = FontVariant {
-            id,
-            family: family.clone(),
-            style: style_str.to_string(),
-            weight: weight,
-            path: path_str,
-            postscript_name: Some(face_info.post_script_name.clone()),
-            full_name,
-            is_system_core,
-        };
+    FontVariant {
+        // Unreadable tables: only the path-based id is left
+        identity: identity.unwrap_or_else(|| id.clone()),
+        name_version,
+        id,
+        family,
+        style: style_str.to_string(),
+        weight,
+        path: path_str,
+        face_index: face_info.index,
+        postscript_name: Some(face_info.post_script_name.clone()),
+        full_name,
+        is_system_core: false,
+        scope: FontScope::Unregistered,
+        registry_value_name: None,
+        installed: true,
+        library_hash: None,
+        classification,
+        metadata,
+        licence,
+    }
+}
 
+fn group_families(variants: Vec<FontVariant>) -> ListFontsResult {
+    let mut families_map: HashMap<String, Vec<FontVariant>> = HashMap::new();
+    for variant in variants {
         families_map
-            .entry(family)
+            .entry(variant.family.clone())
             .or_insert(Vec::new())
             .push(variant);
     }
@@ -226,3 +402,26 @@ pub fn list_fonts() -> ListFontsResult {
         total_variants,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_files_outside_the_fonts_folders_are_loaded_separately() {
+        let root = std::env::temp_dir();
+        let system = root.join("Windows").join("Fonts");
+        let user = root.join("Users").join("me").join("Fonts");
+        let paths = [
+            system.join("arial.ttf"),
+            user.join("sub").join("inter.ttf"),
+            root.join("AppData").join("library").join("ab12").join("lato.ttf"),
+            root.join("fonts-elsewhere").join("mono.otf"),
+        ];
+
+        // Registry data can differ in case from the folder paths
+        let dirs = [PathBuf::from(system.to_string_lossy().to_uppercase()), user];
+        let outside = outside_dirs(paths.iter().map(|p| p.as_path()), &dirs);
+        assert_eq!(outside, [paths[2].as_path(), paths[3].as_path()]);
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use winapi::shared::winerror::{
+    ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, ERROR_LOCK_VIOLATION, ERROR_PATH_NOT_FOUND, ERROR_SHARING_VIOLATION,
+    ERROR_USER_MAPPED_FILE,
+};
+
+// Lets the frontend localise the message and offer a targeted fix
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FontErrorKind {
+    NotFound,
+    UnsupportedFormat,
+    ParseFailed,
+    InvalidFont,
+    AccessDenied,
+    // Some application still has the font loaded ("close apps using this font")
+    FileInUse,
+    RegistryFailure,
+    OutsideAllowedDir,
+    TooLarge,
+    ProtectedSystemFont,
+    NotRegistered,
+    // The target path is taken, e.g. when restoring a quarantined font
+    AlreadyExists,
+    // A tag or collection name that is blank
+    InvalidName,
+    Io,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FontError {
+    pub kind: FontErrorKind,
+    pub path: Option<String>,
+    // Win32 error code, when the failure came from the OS
+    pub os_code: Option<i32>,
+    // English fallback for logs and untranslated kinds
+    pub message: String,
+}
+
+impl FontError {
+    pub fn new(kind: FontErrorKind, path: Option<&Path>, message: impl Into<String>) -> Self {
+        FontError {
+            kind,
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            os_code: None,
+            message: message.into(),
+        }
+    }
+
+    // File system failure; the kind is derived from the OS error code
+    pub fn io(path: &Path, context: &str, err: &io::Error) -> Self {
+        let kind = match err.raw_os_error().map(|c| c as u32) {
+            Some(ERROR_FILE_NOT_FOUND) | Some(ERROR_PATH_NOT_FOUND) => FontErrorKind::NotFound,
+            Some(ERROR_ACCESS_DENIED) => FontErrorKind::AccessDenied,
+            Some(ERROR_SHARING_VIOLATION) | Some(ERROR_LOCK_VIOLATION) | Some(ERROR_USER_MAPPED_FILE) => {
+                FontErrorKind::FileInUse
+            }
+            _ => match err.kind() {
+                io::ErrorKind::NotFound => FontErrorKind::NotFound,
+                io::ErrorKind::PermissionDenied => FontErrorKind::AccessDenied,
+                _ => FontErrorKind::Io,
+            },
+        };
+        FontError {
+            kind,
+            path: Some(path.to_string_lossy().to_string()),
+            os_code: err.raw_os_error(),
+            message: format!("{} {}: {}", context, path.display(), err),
+        }
+    }
+
+    pub fn registry(path: Option<&Path>, context: &str, err: &io::Error) -> Self {
+        FontError {
+            kind: FontErrorKind::RegistryFailure,
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            os_code: err.raw_os_error(),
+            message: format!("{}: {}", context, err),
+        }
+    }
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for FontError {}
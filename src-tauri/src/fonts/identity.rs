@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ttf_parser::Face;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct NameVersionKey {
+    pub postscript_name: String,
+    // Name ID 5, e.g. "Version 2.137"
+    pub version: String,
+}
+
+// Content-derived identity of one face: the same tables give the same identity wherever the file
+// lives and whatever it is called. Faces of a collection differ by the tables they reference.
+pub fn face_identity(face: &Face) -> String {
+    let raw = face.raw_face();
+    let mut records: Vec<_> = raw.table_records.into_iter().collect();
+    records.sort_by_key(|r| r.tag);
+
+    let mut hasher = Sha256::new();
+    for record in records {
+        let start = record.offset as usize;
+        // A table running past the end of the file contributes its tag and length only
+        let bytes = start
+            .checked_add(record.length as usize)
+            .and_then(|end| raw.data.get(start..end))
+            .unwrap_or_default();
+        hasher.update(record.tag.to_bytes());
+        hasher.update(record.length.to_be_bytes());
+        hasher.update(bytes);
+    }
+    format!("c:{}", hex::encode(&hasher.finalize()[..16]))
+}
+
+pub fn name_version_key(face: &Face) -> Option<NameVersionKey> {
+    let name = |id: u16| {
+        face.names()
+            .into_iter()
+            .filter(|n| n.name_id == id && n.is_unicode())
+            .find_map(|n| n.to_string())
+    };
+    Some(NameVersionKey {
+        postscript_name: name(6)?,
+        version: name(5)?,
+    })
+}
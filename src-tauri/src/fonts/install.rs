@@ -1,21 +1,192 @@
 use std::path::{Path, PathBuf};
 use std::fs;
-use winapi::um::winuser::{SendMessageTimeoutW, HWND_BROADCAST, WM_FONTCHANGE, SMTO_ABORTIFHUNG};
-use crate::win::registry::register_font;
-use fontdb::Database;
-use serde::Serialize;
+use std::io;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::fonts::elevation::{check_machine_plan, install_elevated};
+use crate::fonts::enumerate::invalidate_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::plan::{build_plan, InstallPlan, PlannedAction, PlannedInstall};
+use crate::fonts::{system_fonts_dir, user_fonts_dir};
+use crate::fonts::validate::ValidationIssue;
+use crate::win::registry::{delete_font_registry_value, get_font_registry_value, register_font, FontHive};
+use crate::win::{broadcast_font_change, is_elevated};
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::io::Read;
+use ttf_parser::Face;
 
-#[derive(Serialize)]
+// What to do when the destination file already exists with different content
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    Skip,
+    #[default]
+    Overwrite,
+    KeepBoth,
+    UpgradeIfNewer,
+}
+
+// Who gets the fonts
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallScope {
+    // %LOCALAPPDATA%\Microsoft\Windows\Fonts + HKCU
+    #[default]
+    CurrentUser,
+    // %SystemRoot%\Fonts + HKLM; needs an elevated helper process
+    AllUsers,
+}
+
+impl InstallScope {
+    pub fn fonts_dir(self) -> PathBuf {
+        match self {
+            InstallScope::CurrentUser => user_fonts_dir(),
+            InstallScope::AllUsers => system_fonts_dir(),
+        }
+    }
+
+    pub fn hive(self) -> FontHive {
+        match self {
+            InstallScope::CurrentUser => FontHive::CurrentUser,
+            InstallScope::AllUsers => FontHive::LocalMachine,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstallOptions {
+    pub conflict_policy: ConflictPolicy,
+    // All-or-nothing: any failure undoes every file and registry value written by the batch
+    pub atomic: bool,
+    pub scope: InstallScope,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallDecision {
+    Installed,
+    AlreadyInstalled,
+    Overwritten,
+    KeptBoth,
+    Upgraded,
+    Skipped,
+    SkippedNotNewer,
+    SkippedConflict,
+    Failed,
+    RolledBack,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallFileResult {
+    pub path: String,
+    pub decision: InstallDecision,
+    pub destination: Option<String>,
+    pub message: Option<String>,
+    pub error: Option<FontError>,
+}
+
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InstallResult {
     pub success_count: usize,
     pub failed_count: usize,
-    pub errors: Vec<String>,
+    pub skipped_count: usize,
+    pub errors: Vec<FontError>,
+    pub warnings: Vec<ValidationIssue>,
+    pub files: Vec<InstallFileResult>,
+    pub rollback: Option<RollbackReport>,
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RollbackReport {
+    pub removed_files: Vec<String>,
+    pub restored_files: Vec<String>,
+    pub removed_registry_values: Vec<String>,
+    pub restored_registry_values: Vec<String>,
+    pub errors: Vec<FontError>,
+    // The system is back to exactly how it was before the batch
+    pub complete: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallStage {
+    Started,
+    Copied,
+    Registered,
+    Skipped,
+    Failed,
+    RolledBack,
+    Cancelled,
+}
+
+// Payload of the "install-progress" event, one per file and stage
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallProgress {
+    pub batch_id: Option<String>,
+    pub index: usize,
+    pub total: usize,
+    pub path: String,
+    pub stage: InstallStage,
+    pub message: Option<String>,
+}
+
+// Cancellation flags of the batches currently running, by batch id
+#[derive(Default)]
+pub struct InstallTasks {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
-fn calculate_file_hash(path: &Path) -> std::io::Result<String> {
+// Where a running batch reports progress and looks for cancellation
+pub struct ProgressSink<'a> {
+    app: Option<&'a AppHandle>,
+    batch_id: Option<String>,
+    total: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<'a> ProgressSink<'a> {
+    pub fn new(app: Option<&'a AppHandle>, batch_id: Option<String>, total: usize, cancel: Arc<AtomicBool>) -> Self {
+        ProgressSink {
+            app,
+            batch_id,
+            total,
+            cancel,
+        }
+    }
+
+    fn emit(&self, index: usize, path: &str, stage: InstallStage, message: Option<String>) {
+        if let Some(app) = self.app {
+            let _ = app.emit(
+                "install-progress",
+                InstallProgress {
+                    batch_id: self.batch_id.clone(),
+                    index,
+                    total: self.total,
+                    path: path.to_string(),
+                    stage,
+                    message,
+                },
+            );
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+}
+
+pub(crate) fn calculate_file_hash(path: &Path) -> std::io::Result<String> {
     let mut file = fs::File::open(path)?;
     let mut hasher = Sha256::new();
     let mut buffer = [0; 8192];
@@ -30,158 +201,596 @@ fn calculate_file_hash(path: &Path) -> std::io::Result<String> {
 }
 
 #[tauri::command]
-pub async fn install_fonts(paths: Vec<String>) -> InstallResult {
+pub async fn install_fonts(
+    app: AppHandle,
+    paths: Vec<String>,
+    options: Option<InstallOptions>,
+    batch_id: Option<String>,
+) -> InstallResult {
+    // Registered before planning, so validating a large batch can be cancelled too
+    let batch = begin_batch(&app, batch_id);
+    let plan = build_plan(&paths, &options.unwrap_or_default(), Some(&batch.cancel));
+    run_batch(&app, &plan, &batch)
+}
+
+// Runs a plan produced by plan_install exactly as it was shown to the user
+#[tauri::command]
+pub async fn execute_install_plan(app: AppHandle, plan: InstallPlan, batch_id: Option<String>) -> InstallResult {
+    let batch = begin_batch(&app, batch_id);
+    run_batch(&app, &plan, &batch)
+}
+
+// Asks a running batch to stop after the file it is working on
+#[tauri::command]
+pub fn cancel_install(batch_id: String, tasks: State<'_, InstallTasks>) -> bool {
+    match tasks.running.lock().unwrap().get(&batch_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+// A running batch's cancellation flag, registered under its id until dropped
+pub(crate) struct Batch<'a> {
+    app: &'a AppHandle,
+    pub id: String,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl Drop for Batch<'_> {
+    fn drop(&mut self) {
+        self.app.state::<InstallTasks>().running.lock().unwrap().remove(&self.id);
+        // Whatever the batch got done changes what is installed
+        invalidate_variants(self.app);
+    }
+}
+
+// Batches started without an id get one, so the frontend can still cancel them with the id
+// from their first progress event
+pub(crate) fn begin_batch(app: &AppHandle, batch_id: Option<String>) -> Batch<'_> {
+    let id = batch_id.unwrap_or_else(|| {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        format!("batch-{:x}", nanos)
+    });
+    let cancel = Arc::new(AtomicBool::new(false));
+    app.state::<InstallTasks>().running.lock().unwrap().insert(id.clone(), cancel.clone());
+    Batch { app, id, cancel }
+}
+
+pub(crate) fn run_batch(app: &AppHandle, plan: &InstallPlan, batch: &Batch) -> InstallResult {
+    let progress = ProgressSink::new(Some(app), Some(batch.id.clone()), plan.items.len(), batch.cancel.clone());
+    if plan.scope != InstallScope::AllUsers {
+        return execute_plan(plan, &progress, &std::env::temp_dir());
+    }
+    if is_elevated() {
+        // Same checks the helper applies when the app itself already runs elevated
+        return match check_machine_plan(plan) {
+            Ok(()) => execute_plan(plan, &progress, &std::env::temp_dir()),
+            Err(error) => batch_failed(plan, error),
+        };
+    }
+    if progress.is_cancelled() {
+        // Cancelled while planning: no UAC prompt, every file is reported as cancelled
+        return execute_plan(plan, &progress, &std::env::temp_dir());
+    }
+
+    // The helper can't report as it goes, so the per-file events all arrive at the end
+    let result = install_elevated(plan);
+    for (i, file) in result.files.iter().enumerate() {
+        report_outcome(file, i, &progress);
+    }
+    result
+}
+
+// An atomic batch keeps the backups of what it replaces in a fresh folder under `work_dir`
+pub fn execute_plan(plan: &InstallPlan, progress: &ProgressSink, work_dir: &Path) -> InstallResult {
+    let mut warnings = Vec::new();
+    for item in &plan.items {
+        warnings.extend(item.warnings.iter().cloned());
+    }
+
+    // Plans can come from the frontend; they may only write where their scope installs to
+    if !same_path(Path::new(&plan.fonts_dir), &plan.scope.fonts_dir()) {
+        let error = FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            Some(Path::new(&plan.fonts_dir)),
+            format!("{} is not the fonts directory of the install scope", plan.fonts_dir),
+        );
+        return batch_failed(plan, error);
+    }
+
+    // Only create the fonts directory if something is actually going to be copied into it
+    let fonts_dir = Path::new(&plan.fonts_dir);
+    if plan.items.iter().any(|i| i.action.copies_file()) && !fonts_dir.exists() {
+        if let Err(e) = fs::create_dir_all(fonts_dir) {
+             return batch_failed(plan, FontError::io(fonts_dir, "Failed to create fonts directory", &e));
+        }
+    }
+
+    let (files, rollback) = if plan.atomic {
+        execute_atomic(plan, progress, work_dir)
+    } else {
+        let mut files = Vec::new();
+        for (i, item) in plan.items.iter().enumerate() {
+            if progress.is_cancelled() {
+                files.push(cancelled(item, i, progress));
+                continue;
+            }
+            progress.emit(i, &item.source, InstallStage::Started, None);
+            let result = execute_item(item, i, plan.scope, Path::new(&item.source), None, progress);
+            report_outcome(&result, i, progress);
+            files.push(result);
+        }
+        (files, None)
+    };
+    let cancelled = progress.is_cancelled();
+
+    // Broadcast WM_FONTCHANGE
+    broadcast_font_change();
+
     let mut success_count = 0;
     let mut failed_count = 0;
+    let mut skipped_count = 0;
     let mut errors = Vec::new();
+    for result in &files {
+        match result.decision {
+            InstallDecision::Failed => {
+                failed_count += 1;
+                errors.extend(result.error.clone());
+            }
+            InstallDecision::RolledBack => failed_count += 1,
+            InstallDecision::Skipped
+            | InstallDecision::SkippedNotNewer
+            | InstallDecision::SkippedConflict
+            | InstallDecision::Cancelled => skipped_count += 1,
+            _ => success_count += 1,
+        }
+    }
 
-    // Get the user fonts directory: %LOCALAPPDATA%\Microsoft\Windows\Fonts
-    let local_app_data = std::env::var("LOCALAPPDATA").unwrap_or_default();
-    if local_app_data.is_empty() {
-        return InstallResult {
-            success_count: 0,
-            failed_count: paths.len(),
-            errors: vec!["Could not determine LOCALAPPDATA directory".to_string()],
-        };
+    InstallResult {
+        success_count,
+        failed_count,
+        skipped_count,
+        errors,
+        warnings,
+        files,
+        rollback,
+        cancelled,
     }
-    
-    let fonts_dir = PathBuf::from(local_app_data).join("Microsoft").join("Windows").join("Fonts");
-    if !fonts_dir.exists() {
-        if let Err(e) = fs::create_dir_all(&fonts_dir) {
-             return InstallResult {
-                success_count: 0,
-                failed_count: paths.len(),
-                errors: vec![format!("Failed to create fonts directory: {}", e)],
-            };
-        }
+}
+
+// Result for a batch that failed as a whole before any file was looked at
+pub(crate) fn batch_failed(plan: &InstallPlan, error: FontError) -> InstallResult {
+    InstallResult {
+        success_count: 0,
+        failed_count: plan.items.len(),
+        skipped_count: 0,
+        errors: vec![error],
+        warnings: plan.items.iter().flat_map(|i| i.warnings.iter().cloned()).collect(),
+        files: Vec::new(),
+        rollback: None,
+        cancelled: false,
+    }
+}
+
+fn execute_atomic(
+    plan: &InstallPlan,
+    progress: &ProgressSink,
+    work_dir: &Path,
+) -> (Vec<InstallFileResult>, Option<RollbackReport>) {
+    // An invalid entry fails the batch before anything is touched
+    if let Some(bad) = plan.items.iter().position(|i| i.action == PlannedAction::Invalid) {
+        let error = invalid_error(&plan.items[bad]);
+        return (abort_batch(plan, bad, error, progress), None);
     }
 
-    for path_str in paths {
-        let path = Path::new(&path_str);
-        if !path.exists() {
-            failed_count += 1;
-            errors.push(format!("File not found: {}", path_str));
-            continue;
+    let mut journal = match Journal::new(work_dir) {
+        Ok(journal) => journal,
+        Err(e) => {
+            let error = FontError::io(work_dir, "Failed to create staging directory in", &e);
+            return (abort_batch(plan, 0, error, progress), None);
         }
+    };
 
-        // Basic validation
-        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
-        if !matches!(ext.as_deref(), Some("ttf") | Some("otf")) {
-            failed_count += 1;
-            errors.push(format!("Unsupported file type: {}", path_str));
+    // 1. Stage: copy every source aside first, so unreadable sources fail before the fonts folder is touched
+    let mut staged = Vec::new();
+    for (i, item) in plan.items.iter().enumerate() {
+        if progress.is_cancelled() {
+            journal.cleanup();
+            let files = plan.items.iter().enumerate().map(|(i, item)| cancelled(item, i, progress)).collect();
+            return (files, None);
+        }
+        if !item.action.copies_file() {
+            staged.push(PathBuf::from(&item.source));
             continue;
         }
-        
-        // Parse font metadata to get family name (needed for registry key)
-        // Optimization: scope the Database usage to ensure file handle is dropped as soon as possible
-        let family = {
-            let mut db = Database::new();
-            if let Err(e) = db.load_font_file(path) {
-                 failed_count += 1;
-                 errors.push(format!("Failed to parse font file {}: {}", path_str, e));
-                 continue;
-            }
-            
-            if db.faces().count() == 0 {
-                 failed_count += 1;
-                 errors.push(format!("No font faces found in file: {}", path_str));
-                 continue;
+        let staged_path = journal.staging_dir.join(format!("{}.stage", i));
+        if let Err(e) = fs::copy(&item.source, &staged_path) {
+            journal.cleanup();
+            let error = FontError::io(Path::new(&item.source), "Failed to stage file", &e);
+            return (abort_batch(plan, i, error, progress), None);
+        }
+        staged.push(staged_path);
+    }
+
+    // 2. Commit, journaling everything that gets replaced. Cancelling here undoes the batch like a failure.
+    let mut files = Vec::new();
+    let mut stopped_at = None;
+    for (i, item) in plan.items.iter().enumerate() {
+        if progress.is_cancelled() {
+            stopped_at = Some(i);
+            break;
+        }
+        progress.emit(i, &item.source, InstallStage::Started, None);
+        let result = execute_item(item, i, plan.scope, &staged[i], Some(&mut journal), progress);
+        report_outcome(&result, i, progress);
+        let failed = result.decision == InstallDecision::Failed;
+        files.push(result);
+        if failed {
+            stopped_at = Some(i + 1);
+            break;
+        }
+    }
+
+    let rollback = stopped_at.map(|stopped_at| {
+        let report = journal.rollback();
+        for (i, result) in files.iter_mut().enumerate() {
+            if !matches!(
+                result.decision,
+                InstallDecision::Failed
+                    | InstallDecision::Skipped
+                    | InstallDecision::SkippedNotNewer
+                    | InstallDecision::SkippedConflict
+            ) {
+                result.decision = InstallDecision::RolledBack;
+                progress.emit(i, &result.path, InstallStage::RolledBack, None);
             }
+        }
+        for (i, item) in plan.items.iter().enumerate().skip(stopped_at) {
+            files.push(if progress.is_cancelled() {
+                cancelled(item, i, progress)
+            } else {
+                not_attempted(item)
+            });
+        }
+        report
+    });
 
-            // Use the first face to determine the registry name
-            let face = db.faces().next().unwrap();
-            face.families.first().map(|f| f.0.clone()).unwrap_or("Unknown".to_string())
-        };
-        
-        // Construct the registry value name, e.g., "Arial (TrueType)"
-        // This is a simplification; handling multiple faces in a single file requires more complex logic
-        let registry_name = format!("{} (TrueType)", family); // Simplified
-        
-        let file_name = path.file_name().unwrap();
-        let dest_path = fonts_dir.join(file_name);
-
-        // Check if destination file already exists
-        if dest_path.exists() {
-            // Compare source and destination to see if they are the same file
-            // First check file size
-            let mut is_identical = false;
-            if let (Ok(src_meta), Ok(dest_meta)) = (fs::metadata(path), fs::metadata(&dest_path)) {
-                if src_meta.len() == dest_meta.len() {
-                    // Size matches, calculate hash
-                    if let (Ok(src_hash), Ok(dest_hash)) = (calculate_file_hash(path), calculate_file_hash(&dest_path)) {
-                        if src_hash == dest_hash {
-                            is_identical = true;
-                        }
-                    }
+    journal.cleanup();
+    (files, rollback)
+}
+
+// Result list for a batch that stopped at `failed_at` before changing anything
+fn abort_batch(plan: &InstallPlan, failed_at: usize, error: FontError, progress: &ProgressSink) -> Vec<InstallFileResult> {
+    progress.emit(failed_at, &plan.items[failed_at].source, InstallStage::Failed, Some(error.message.clone()));
+    plan.items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if i == failed_at {
+                InstallFileResult {
+                    path: item.source.clone(),
+                    decision: InstallDecision::Failed,
+                    destination: item.destination.clone(),
+                    message: Some(error.message.clone()),
+                    error: Some(error.clone()),
                 }
+            } else {
+                not_attempted(item)
             }
+        })
+        .collect()
+}
 
-            if is_identical {
-                 // File already exists and seems identical, consider it a success or skip
-                 // We still proceed to register in registry just in case
-                 if let Err(e) = register_font(&registry_name, dest_path.to_str().unwrap()) {
-                    failed_count += 1;
-                    errors.push(format!("Failed to register font {}: {}", path_str, e));
-                 } else {
-                    success_count += 1;
-                 }
-                 continue;
-            }
-            
-            // If exists but different, try to overwrite.
-            // fs::copy will attempt to overwrite, but if file is in use (loaded by OS), it will fail with "Text file busy" or "Access denied"
-        }
-
-        // Copy file
-        // Retry logic for file copy to handle temporary file locks
-        let mut copy_success = false;
-        for _ in 0..3 {
-            if let Ok(_) = fs::copy(path, &dest_path) {
-                copy_success = true;
-                break;
+fn cancelled(item: &PlannedInstall, index: usize, progress: &ProgressSink) -> InstallFileResult {
+    progress.emit(index, &item.source, InstallStage::Cancelled, None);
+    InstallFileResult {
+        path: item.source.clone(),
+        decision: InstallDecision::Cancelled,
+        destination: item.destination.clone(),
+        message: Some("Cancelled before this file was installed".to_string()),
+        error: None,
+    }
+}
+
+// Final event for a file, derived from what happened to it
+fn report_outcome(result: &InstallFileResult, index: usize, progress: &ProgressSink) {
+    let stage = match result.decision {
+        InstallDecision::Failed => InstallStage::Failed,
+        InstallDecision::Skipped | InstallDecision::SkippedNotNewer | InstallDecision::SkippedConflict => {
+            InstallStage::Skipped
+        }
+        _ => InstallStage::Registered,
+    };
+    progress.emit(index, &result.path, stage, result.message.clone());
+}
+
+// Plans always carry an error for invalid entries; older or hand-made plans may not
+fn invalid_error(item: &PlannedInstall) -> FontError {
+    item.error.clone().unwrap_or_else(|| {
+        FontError::new(
+            FontErrorKind::InvalidFont,
+            Some(Path::new(&item.source)),
+            item.message.clone().unwrap_or_default(),
+        )
+    })
+}
+
+fn not_attempted(item: &PlannedInstall) -> InstallFileResult {
+    InstallFileResult {
+        path: item.source.clone(),
+        decision: InstallDecision::Skipped,
+        destination: item.destination.clone(),
+        message: Some("Not attempted: the batch was aborted".to_string()),
+        error: None,
+    }
+}
+
+fn execute_item(
+    item: &PlannedInstall,
+    index: usize,
+    scope: InstallScope,
+    source: &Path,
+    mut journal: Option<&mut Journal>,
+    progress: &ProgressSink,
+) -> InstallFileResult {
+    let outcome = |decision: InstallDecision, message: Option<String>| InstallFileResult {
+        path: item.source.clone(),
+        decision,
+        destination: item.destination.clone(),
+        message,
+        error: None,
+    };
+    let failed = |error: FontError| InstallFileResult {
+        message: Some(error.message.clone()),
+        error: Some(error),
+        ..outcome(InstallDecision::Failed, None)
+    };
+
+    let decision = match item.action {
+        PlannedAction::Invalid => return failed(invalid_error(item)),
+        PlannedAction::Skip => return outcome(InstallDecision::Skipped, item.message.clone()),
+        PlannedAction::SkipNotNewer => return outcome(InstallDecision::SkippedNotNewer, item.message.clone()),
+        PlannedAction::Conflict => return outcome(InstallDecision::SkippedConflict, item.message.clone()),
+        PlannedAction::SkipIdentical => InstallDecision::AlreadyInstalled,
+        PlannedAction::Install => InstallDecision::Installed,
+        PlannedAction::Overwrite => InstallDecision::Overwritten,
+        PlannedAction::KeepBoth => InstallDecision::KeptBoth,
+        PlannedAction::Upgrade => InstallDecision::Upgraded,
+    };
+
+    let (Some(dest_str), Some(registry_name)) = (&item.destination, &item.registry_name) else {
+        return failed(FontError::new(
+            FontErrorKind::InvalidFont,
+            Some(Path::new(&item.source)),
+            format!("Install plan entry for {} has no destination", item.source),
+        ));
+    };
+    let dest_path = Path::new(dest_str);
+    if let Err(error) = check_item(item, scope, dest_path, registry_name) {
+        return failed(error);
+    }
+
+    if item.action.copies_file() {
+        if let Some(journal) = journal.as_deref_mut() {
+            if let Err(e) = journal.record_file(dest_path) {
+                return failed(FontError::io(dest_path, "Failed to back up", &e));
             }
-            // Wait a bit before retrying
-            std::thread::sleep(std::time::Duration::from_millis(100));
         }
 
-        if !copy_success {
-            failed_count += 1;
-            // Capture the last error for reporting
-            let err = fs::copy(path, &dest_path).err().unwrap();
-            errors.push(format!("Failed to copy file {}: {}", path_str, err));
-            continue;
+        if let Err(err) = copy_with_retry(source, dest_path) {
+            return failed(FontError::io(dest_path, "Failed to copy file to", &err));
         }
+        progress.emit(index, &item.source, InstallStage::Copied, None);
+    }
 
-        // Register in registry
-        // Note: For user-installed fonts in %LOCALAPPDATA%, the registry value should be the full path or relative path?
-        // Usually, for user fonts, it's the full path.
-        if let Err(e) = register_font(&registry_name, dest_path.to_str().unwrap()) {
-             failed_count += 1;
-             errors.push(format!("Failed to register font {}: {}", path_str, e));
+    let hive = scope.hive();
+    if let Some(journal) = journal.as_deref_mut() {
+        match get_font_registry_value(hive, registry_name) {
+            Ok(previous) => journal.registry.push((hive, registry_name.clone(), previous)),
+            Err(e) => {
+                let context = format!("Failed to read registry value {}", registry_name);
+                return failed(FontError::registry(Some(dest_path), &context, &e));
+            }
+        }
+    }
+
+    // Register in registry (for identical files we still re-register just in case)
+    // Per-user values hold the full path, since %LOCALAPPDATA% isn't where Windows resolves bare names.
+    // Machine-wide fonts follow the Windows convention of a bare file name relative to %SystemRoot%\Fonts.
+    let registry_data = match scope {
+        InstallScope::CurrentUser => dest_str.clone(),
+        InstallScope::AllUsers => dest_path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+    };
+    if let Err(e) = register_font(hive, registry_name, &registry_data) {
+         // In atomic mode the journal puts the file back; otherwise remove the copy ourselves
+         if item.action.copies_file() && journal.is_none() {
              // Cleanup: remove copied file
-             let _ = fs::remove_file(&dest_path);
-             continue;
+             let _ = fs::remove_file(dest_path);
+         }
+         let context = format!("Failed to register font {}", item.source);
+         return failed(FontError::registry(Some(dest_path), &context, &e));
+    }
+
+    outcome(decision, None)
+}
+
+// Refuses plan entries that write outside the scope's fonts directory, use a value name the
+// planner wouldn't have made, or whose destination changed since planning
+fn check_item(item: &PlannedInstall, scope: InstallScope, dest: &Path, registry_name: &str) -> Result<(), FontError> {
+    let fonts_dir = scope.fonts_dir();
+    // file_name() also rules out "..", which parent() would otherwise accept
+    if dest.file_name().is_none() || !dest.parent().is_some_and(|parent| same_path(parent, &fonts_dir)) {
+        return Err(FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            Some(dest),
+            format!("{} is outside {}", dest.display(), fonts_dir.display()),
+        ));
+    }
+
+    if !is_font_registry_name(registry_name) {
+        return Err(FontError::new(
+            FontErrorKind::InvalidFont,
+            Some(Path::new(&item.source)),
+            format!("\"{}\" is not a font registry value name", registry_name),
+        ));
+    }
+
+    let current = if dest.exists() {
+        Some(calculate_file_hash(dest).map_err(|e| FontError::io(dest, "Failed to read", &e))?)
+    } else {
+        None
+    };
+    if current != item.destination_hash {
+        let what = if item.destination_hash.is_none() { "appeared" } else { "changed" };
+        let message = format!("{} {} since the install was planned", dest.display(), what);
+        return Err(FontError::new(FontErrorKind::AlreadyExists, Some(dest), message));
+    }
+    Ok(())
+}
+
+// "<name> (TrueType)", the only form the planner produces
+pub(crate) fn is_font_registry_name(name: &str) -> bool {
+    name.strip_suffix(" (TrueType)")
+        .is_some_and(|name| !name.trim().is_empty() && !name.chars().any(char::is_control))
+}
+
+// A face's full name (name ID 4), which Windows names a font's registry value after
+pub(crate) fn full_font_name(face: &Face) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|n| n.name_id == 4 && n.is_unicode())
+        .filter_map(|n| Some((n.language_id == 0x0409, n.to_string()?)))
+        .max_by_key(|(english, _)| *english)
+        .map(|(_, name)| name.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|name| !name.trim().is_empty())
+}
+
+// "<name> (TrueType)", or "<name> [<tag>] (TrueType)" and then numbered forms of it while the
+// name is taken. `taken` must compare case-insensitively, like the registry does.
+pub(crate) fn unique_registry_name(name: &str, tag: &str, taken: impl Fn(&str) -> bool) -> String {
+    let plain = format!("{} (TrueType)", name);
+    if !taken(&plain) {
+        return plain;
+    }
+    (1..)
+        .map(|n| match n {
+            1 => format!("{} [{}] (TrueType)", name, tag),
+            n => format!("{} [{} {}] (TrueType)", name, tag, n),
+        })
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+// Windows paths compare case-insensitively, with or without a trailing separator
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+    let normalize = |p: &Path| p.to_string_lossy().trim_end_matches('\\').to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+// Retry logic for file copy to handle temporary file locks
+// Overwriting: fs::copy will fail with "Access denied" if the file is in use (loaded by OS)
+fn copy_with_retry(source: &Path, dest: &Path) -> io::Result<()> {
+    for _ in 0..3 {
+        if fs::copy(source, dest).is_ok() {
+            return Ok(());
         }
+        // Wait a bit before retrying
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    // Capture the last error for reporting
+    fs::copy(source, dest).map(|_| ())
+}
+
+// Undo log for an atomic batch
+struct Journal {
+    staging_dir: PathBuf,
+    // Destination written, plus where the file it replaced was backed up to
+    files: Vec<(PathBuf, Option<PathBuf>)>,
+    // Registry value written, plus its data before the batch
+    registry: Vec<(FontHive, String, Option<String>)>,
+}
 
-        success_count += 1;
+impl Journal {
+    fn new(work_dir: &Path) -> io::Result<Self> {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let staging_dir = work_dir.join(format!("font-manager-install-{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&staging_dir)?;
+        Ok(Journal {
+            staging_dir,
+            files: Vec::new(),
+            registry: Vec::new(),
+        })
     }
 
-    // Broadcast WM_FONTCHANGE
-    unsafe {
-        SendMessageTimeoutW(
-            HWND_BROADCAST,
-            WM_FONTCHANGE,
-            0,
-            0,
-            SMTO_ABORTIFHUNG,
-            1000,
-            std::ptr::null_mut(),
-        );
+    fn record_file(&mut self, dest: &Path) -> io::Result<()> {
+        let backup = if dest.exists() {
+            let backup = self.staging_dir.join(format!("{}.backup", self.files.len()));
+            fs::copy(dest, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+        self.files.push((dest.to_path_buf(), backup));
+        Ok(())
     }
 
-    InstallResult {
-        success_count,
-        failed_count,
-        errors,
+    // Undo in reverse order so repeated writes to the same value end up at the oldest state
+    fn rollback(&mut self) -> RollbackReport {
+        let mut report = RollbackReport::default();
+
+        for (hive, name, previous) in self.registry.drain(..).rev() {
+            let result = match &previous {
+                Some(data) => register_font(hive, &name, data),
+                None => delete_font_registry_value(hive, &name),
+            };
+            match result {
+                Ok(()) if previous.is_some() => report.restored_registry_values.push(name),
+                Ok(()) => report.removed_registry_values.push(name),
+                Err(e) => {
+                    let context = format!("Failed to restore registry value {}", name);
+                    report.errors.push(FontError::registry(None, &context, &e))
+                }
+            }
+        }
+
+        for (dest, backup) in self.files.drain(..).rev() {
+            let dest_str = dest.to_string_lossy().to_string();
+            match backup {
+                Some(backup) => match copy_with_retry(&backup, &dest) {
+                    Ok(()) => report.restored_files.push(dest_str),
+                    Err(e) => report.errors.push(FontError::io(&dest, "Failed to restore", &e)),
+                },
+                None => match fs::remove_file(&dest) {
+                    Ok(()) => report.removed_files.push(dest_str),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => report.errors.push(FontError::io(&dest, "Failed to remove", &e)),
+                },
+            }
+        }
+
+        report.complete = report.errors.is_empty();
+        report
+    }
+
+    fn cleanup(&self) {
+        let _ = fs::remove_dir_all(&self.staging_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_registry_name_adds_the_tag_then_numbers() {
+        let taken = ["Inter Bold (TrueType)", "inter bold [Inter-Bold] (truetype)"];
+        let is_taken = |name: &str| taken.iter().any(|t| t.eq_ignore_ascii_case(name));
+        assert_eq!(unique_registry_name("Inter", "Inter-Bold", is_taken), "Inter (TrueType)");
+        assert_eq!(unique_registry_name("Inter Bold", "Inter-Bold", is_taken), "Inter Bold [Inter-Bold 2] (TrueType)");
+        assert_eq!(unique_registry_name("Inter Bold", "Inter-Bold (2)", is_taken), "Inter Bold [Inter-Bold (2)] (TrueType)");
+        assert!(is_font_registry_name(&unique_registry_name("Inter Bold", "x", is_taken)));
     }
 }
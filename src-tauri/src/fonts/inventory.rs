@@ -0,0 +1,426 @@
+use crate::fonts::classify::FontClass;
+use crate::fonts::enumerate::installed_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::install::calculate_file_hash;
+use crate::fonts::licence::EmbeddingLevel;
+use crate::fonts::FontScope;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InventoryFormat {
+    Csv,
+    Json,
+}
+
+// One installed face, flattened for audits
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryEntry {
+    pub family: String,
+    pub full_name: Option<String>,
+    pub postscript_name: Option<String>,
+    pub style: String,
+    pub weight: u16,
+    pub version: Option<String>,
+    pub vendor: Option<String>,
+    pub scope: FontScope,
+    pub registry_value_name: Option<String>,
+    pub path: String,
+    pub face_index: u32,
+    pub size: Option<u64>,
+    // SHA-256 of the whole file
+    pub hash: Option<String>,
+    pub embedding: Option<EmbeddingLevel>,
+    pub no_subsetting: bool,
+    pub bitmap_only: bool,
+    pub licence_url: Option<String>,
+    pub classification: FontClass,
+    pub identity: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Inventory {
+    // Unix timestamp in seconds
+    pub generated_at: u64,
+    pub computer_name: Option<String>,
+    pub fonts: Vec<InventoryEntry>,
+}
+
+// Fonts present on both sides under the same PostScript name, with a different file or version
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryChange {
+    pub key: String,
+    pub before: Vec<InventoryEntry>,
+    pub after: Vec<InventoryEntry>,
+    pub hash_changed: bool,
+    pub version_changed: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryDiff {
+    // Only in the second inventory
+    pub added: Vec<InventoryEntry>,
+    // Only in the first
+    pub removed: Vec<InventoryEntry>,
+    pub changed: Vec<InventoryChange>,
+    // Keys present on both sides with nothing changed
+    pub unchanged: usize,
+}
+
+const CSV_HEADER: [&str; 19] = [
+    "family",
+    "fullName",
+    "postscriptName",
+    "style",
+    "weight",
+    "version",
+    "vendor",
+    "scope",
+    "registryValueName",
+    "path",
+    "faceIndex",
+    "size",
+    "hash",
+    "embedding",
+    "noSubsetting",
+    "bitmapOnly",
+    "licenceUrl",
+    "classification",
+    "identity",
+];
+
+// Spreadsheets run cells starting with these as formulas; such fields are written with a leading '.
+// A field that really starts with ' gets one too, so reading strips exactly one.
+const FORMULA_PREFIXES: [char; 7] = ['=', '+', '-', '@', '\t', '\r', '\''];
+
+// Writes every installed face to a CSV or JSON file; returns how many were written
+#[tauri::command]
+pub async fn export_inventory(format: InventoryFormat, path: String) -> Result<usize, FontError> {
+    let inventory = live_inventory();
+    let p = Path::new(&path);
+    let data = match format {
+        InventoryFormat::Json => serde_json::to_vec_pretty(&inventory).map_err(|e| {
+            FontError::new(
+                FontErrorKind::Io,
+                Some(p),
+                format!("Failed to serialize inventory: {}", e),
+            )
+        })?,
+        InventoryFormat::Csv => to_csv(&inventory.fonts).into_bytes(),
+    };
+    fs::write(p, data).map_err(|e| FontError::io(p, "Failed to write", &e))?;
+    Ok(inventory.fonts.len())
+}
+
+// Compares two exported inventories, CSV or JSON; without a second file the first is compared to the
+// fonts installed right now
+#[tauri::command]
+pub async fn diff_inventories(base: String, other: Option<String>) -> Result<InventoryDiff, FontError> {
+    let before = read_inventory(Path::new(&base))?;
+    let after = match other {
+        Some(other) => read_inventory(Path::new(&other))?,
+        None => live_inventory().fonts,
+    };
+    Ok(diff(before, after))
+}
+
+pub fn live_inventory() -> Inventory {
+    // Collections share one hash
+    let mut files: HashMap<String, (Option<u64>, Option<String>)> = HashMap::new();
+    let mut fonts: Vec<InventoryEntry> = installed_variants()
+        .into_iter()
+        .map(|v| {
+            let (size, hash) = files
+                .entry(v.path.clone())
+                .or_insert_with(|| {
+                    let path = Path::new(&v.path);
+                    (fs::metadata(path).map(|m| m.len()).ok(), calculate_file_hash(path).ok())
+                })
+                .clone();
+            InventoryEntry {
+                family: v.family,
+                full_name: v.full_name,
+                postscript_name: v.postscript_name,
+                style: v.style,
+                weight: v.weight,
+                version: v.metadata.version,
+                vendor: v.metadata.vendor,
+                scope: v.scope,
+                registry_value_name: v.registry_value_name,
+                path: v.path,
+                face_index: v.face_index,
+                size,
+                hash,
+                embedding: v.licence.embedding,
+                no_subsetting: v.licence.no_subsetting,
+                bitmap_only: v.licence.bitmap_only,
+                licence_url: v.licence.url,
+                classification: v.classification,
+                identity: v.identity,
+            }
+        })
+        .collect();
+    fonts.sort_by(|a, b| a.family.cmp(&b.family).then_with(|| a.path.cmp(&b.path)));
+
+    Inventory {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        computer_name: std::env::var("COMPUTERNAME").ok(),
+        fonts,
+    }
+}
+
+fn diff(before: Vec<InventoryEntry>, after: Vec<InventoryEntry>) -> InventoryDiff {
+    // Faces are matched by PostScript name; a name can repeat when duplicate copies are installed
+    fn by_key(entries: Vec<InventoryEntry>) -> BTreeMap<String, Vec<InventoryEntry>> {
+        let mut map: BTreeMap<String, Vec<InventoryEntry>> = BTreeMap::new();
+        for entry in entries {
+            let key = match &entry.postscript_name {
+                Some(name) if !name.is_empty() => name.clone(),
+                _ => format!("{}|{}|{}", entry.family, entry.style, entry.weight),
+            };
+            map.entry(key).or_default().push(entry);
+        }
+        map
+    }
+    // Entries whose value is unknown don't count either way
+    fn values(entries: &[InventoryEntry], field: fn(&InventoryEntry) -> &Option<String>) -> BTreeSet<&str> {
+        entries.iter().filter_map(|e| field(e).as_deref()).collect()
+    }
+    fn differs(a: &[InventoryEntry], b: &[InventoryEntry], field: fn(&InventoryEntry) -> &Option<String>) -> bool {
+        let (a, b) = (values(a, field), values(b, field));
+        !a.is_empty() && !b.is_empty() && a != b
+    }
+
+    let mut before = by_key(before);
+    let mut result = InventoryDiff {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+    for (key, after) in by_key(after) {
+        match before.remove(&key) {
+            None => result.added.extend(after),
+            Some(before) => {
+                let hash_changed = differs(&before, &after, |e| &e.hash);
+                let version_changed = differs(&before, &after, |e| &e.version);
+                if hash_changed || version_changed {
+                    result.changed.push(InventoryChange {
+                        key,
+                        before,
+                        after,
+                        hash_changed,
+                        version_changed,
+                    });
+                } else {
+                    result.unchanged += 1;
+                }
+            }
+        }
+    }
+    result.removed = before.into_values().flatten().collect();
+    result
+}
+
+fn read_inventory(path: &Path) -> Result<Vec<InventoryEntry>, FontError> {
+    let text = fs::read_to_string(path).map_err(|e| FontError::io(path, "Failed to read", &e))?;
+    let parse_error = |e: serde_json::Error| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(path),
+            format!("Failed to parse {}: {}", path.display(), e),
+        )
+    };
+
+    let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    if !is_csv {
+        return serde_json::from_str::<Inventory>(&text)
+            .map(|i| i.fonts)
+            .map_err(parse_error);
+    }
+
+    let mut rows = parse_csv(&text).into_iter();
+    let header = rows.next().unwrap_or_default();
+    rows.filter(|row| row.iter().any(|f| !f.is_empty()))
+        .map(|row| {
+            // Rebuild the JSON shape so serde does the typing and reports missing columns
+            let object: serde_json::Map<String, serde_json::Value> = header
+                .iter()
+                .zip(row)
+                .map(|(column, field)| {
+                    let value = match column.as_str() {
+                        _ if field.is_empty() => serde_json::Value::Null,
+                        "weight" | "faceIndex" | "size" => field
+                            .parse::<u64>()
+                            .map(serde_json::Value::from)
+                            .unwrap_or(serde_json::Value::String(field)),
+                        "noSubsetting" | "bitmapOnly" => serde_json::Value::Bool(field == "true"),
+                        _ => serde_json::Value::String(field),
+                    };
+                    (column.clone(), value)
+                })
+                .collect();
+            serde_json::from_value(serde_json::Value::Object(object)).map_err(parse_error)
+        })
+        .collect()
+}
+
+// Reads what push_row writes
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(unescape_formula(std::mem::take(&mut field))),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(unescape_formula(std::mem::take(&mut field)));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(unescape_formula(field));
+        rows.push(row);
+    }
+    rows
+}
+
+fn unescape_formula(field: String) -> String {
+    let mut chars = field.chars();
+    match (chars.next(), chars.next()) {
+        (Some('\''), Some(c)) if FORMULA_PREFIXES.contains(&c) => field[1..].to_string(),
+        _ => field,
+    }
+}
+
+fn to_csv(fonts: &[InventoryEntry]) -> String {
+    // Enums are written the way serde names them, so the CSV matches the JSON
+    fn name<T: Serialize>(value: &T) -> String {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(s)) => s,
+            _ => String::new(),
+        }
+    }
+    fn opt<T: ToString>(value: &Option<T>) -> String {
+        value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    let mut out = String::new();
+    push_row(&mut out, CSV_HEADER.iter().map(|h| h.to_string()));
+    for f in fonts {
+        push_row(
+            &mut out,
+            [
+                f.family.clone(),
+                opt(&f.full_name),
+                opt(&f.postscript_name),
+                f.style.clone(),
+                f.weight.to_string(),
+                opt(&f.version),
+                opt(&f.vendor),
+                name(&f.scope),
+                opt(&f.registry_value_name),
+                f.path.clone(),
+                f.face_index.to_string(),
+                opt(&f.size),
+                opt(&f.hash),
+                f.embedding.as_ref().map(name).unwrap_or_default(),
+                f.no_subsetting.to_string(),
+                f.bitmap_only.to_string(),
+                opt(&f.licence_url),
+                name(&f.classification),
+                f.identity.clone(),
+            ],
+        );
+    }
+    out
+}
+
+// RFC 4180: fields with commas, quotes or line breaks are quoted, quotes doubled; CRLF line ends
+fn push_row(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (i, mut field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.starts_with(FORMULA_PREFIXES) {
+            field.insert(0, '\'');
+        }
+        if field.contains([',', '"', '\r', '\n']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(family: &str, full_name: &str, path: &str) -> InventoryEntry {
+        InventoryEntry {
+            family: family.to_string(),
+            full_name: Some(full_name.to_string()),
+            postscript_name: Some("Test-Regular".to_string()),
+            style: "normal".to_string(),
+            weight: 400,
+            version: Some("Version 1.000".to_string()),
+            vendor: None,
+            scope: FontScope::PerUser,
+            registry_value_name: Some("Test (TrueType)".to_string()),
+            path: path.to_string(),
+            face_index: 0,
+            size: Some(1234),
+            hash: None,
+            embedding: Some(EmbeddingLevel::Editable),
+            no_subsetting: false,
+            bitmap_only: true,
+            licence_url: None,
+            classification: FontClass::Sans,
+            identity: "c:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn csv_round_trips_awkward_fields() {
+        let fonts = vec![
+            entry("Quoted \"Family\"", "Comma, Separated", "C:\\Fonts\\a.ttf"),
+            entry("=HYPERLINK(\"http://example.com\")", "Line\nbreak", "C:\\Fonts\\b.ttf"),
+            entry("+1", "-minus", "@at"),
+            entry("'quoted", "\ttab", "'=already escaped"),
+        ];
+        let csv = to_csv(&fonts);
+        assert!(csv.contains("\"'=HYPERLINK(\"\"http://example.com\"\")\""));
+        assert!(csv.contains(",'@at,"));
+
+        let path = std::env::temp_dir().join(format!("font-manager-inventory-test-{}.csv", std::process::id()));
+        fs::write(&path, &csv).unwrap();
+        let read = read_inventory(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(read.unwrap(), fonts);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::fonts::activation::sets_by_hash;
+use crate::fonts::enumerate::{describe_face, invalidate_variants};
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::install::calculate_file_hash;
+use crate::fonts::persist::{app_data_dir, read_json, write_json};
+use crate::fonts::validate::validate_font_file;
+use crate::fonts::{FontScope, FontVariant};
+use fontdb::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const CATALOG_FILE: &str = "catalog.json";
+
+// Serialises read-modify-write cycles on the catalog
+static CATALOG_LOCK: Mutex<()> = Mutex::new(());
+
+// A font file stored in the app's library: <app data>/library/<sha256>.<ext>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryFile {
+    pub hash: String,
+    pub path: String,
+    pub file_name: String,
+    pub family: String,
+    pub postscript_names: Vec<String>,
+}
+
+// What the catalog knows about a library file, independent of whether it is installed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    #[serde(flatten)]
+    pub file: LibraryFile,
+    // Where the file was imported from; it may have moved or gone since
+    pub source_path: String,
+    pub size: u64,
+    // Unix timestamp in seconds
+    pub imported_at: u64,
+    // Described once at import time, so listing the library never opens its files
+    pub faces: Vec<FontVariant>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct LibraryCatalog {
+    fonts: Vec<CatalogEntry>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryImportResult {
+    pub imported: Vec<CatalogEntry>,
+    pub errors: Vec<FontError>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryRemoveResult {
+    pub hash: String,
+    pub success: bool,
+    pub error: Option<FontError>,
+}
+
+#[tauri::command]
+pub async fn import_to_library(app: AppHandle, paths: Vec<String>) -> LibraryImportResult {
+    let mut result = LibraryImportResult {
+        imported: Vec::new(),
+        errors: Vec::new(),
+    };
+    for path in paths {
+        match import_entry(&app, Path::new(&path)) {
+            Ok(entry) => result.imported.push(entry),
+            Err(error) => result.errors.push(error),
+        }
+    }
+    invalidate_variants(&app);
+    result
+}
+
+#[tauri::command]
+pub fn list_library(app: AppHandle) -> Result<Vec<CatalogEntry>, FontError> {
+    let _guard = CATALOG_LOCK.lock().unwrap();
+    Ok(load(&app)?.fonts)
+}
+
+// Deletes files from the library; fonts that belong to an activation set are kept
+#[tauri::command]
+pub fn remove_from_library(app: AppHandle, hashes: Vec<String>) -> Result<Vec<LibraryRemoveResult>, FontError> {
+    // Held until the files are gone, so no set can pick one up in between
+    let (_sets_guard, in_sets) = sets_by_hash(&app)?;
+    let _guard = CATALOG_LOCK.lock().unwrap();
+    let mut catalog = load(&app)?;
+
+    let mut results = Vec::new();
+    for hash in hashes {
+        let error = match catalog.fonts.iter().position(|e| e.file.hash == hash) {
+            None => Some(FontError::new(
+                FontErrorKind::NotFound,
+                None,
+                format!("No library font with hash {}", hash),
+            )),
+            Some(index) => {
+                let path = PathBuf::from(&catalog.fonts[index].file.path);
+                if let Some(sets) = in_sets.get(&hash) {
+                    Some(FontError::new(
+                        FontErrorKind::FileInUse,
+                        Some(&path),
+                        format!("Used by activation sets: {}", sets.join(", ")),
+                    ))
+                } else {
+                    match fs::remove_file(&path) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                            Some(FontError::io(&path, "Failed to delete", &e))
+                        }
+                        _ => {
+                            catalog.fonts.remove(index);
+                            None
+                        }
+                    }
+                }
+            }
+        };
+        results.push(LibraryRemoveResult {
+            hash,
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    invalidate_variants(&app);
+    save(&app, &catalog)?;
+    Ok(results)
+}
+
+pub fn library_dir(app: &AppHandle) -> Result<PathBuf, FontError> {
+    let dir = app_data_dir(app)?.join("library");
+    fs::create_dir_all(&dir).map_err(|e| FontError::io(&dir, "Failed to create directory", &e))?;
+    Ok(dir)
+}
+
+// Copies a font into the library. Files are stored by content, so importing the same font twice is free.
+pub fn import_font(app: &AppHandle, source: &Path) -> Result<LibraryFile, FontError> {
+    import_entry(app, source).map(|entry| entry.file)
+}
+
+fn import_entry(app: &AppHandle, source: &Path) -> Result<CatalogEntry, FontError> {
+    let ext = source.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+    let ext = match ext.as_deref() {
+        Some(ext @ ("ttf" | "otf" | "ttc" | "otc")) => ext.to_string(),
+        _ => {
+            return Err(FontError::new(
+                FontErrorKind::UnsupportedFormat,
+                Some(source),
+                format!("Unsupported file type: {}", source.display()),
+            ))
+        }
+    };
+
+    // Same health check as installs; broken files never reach the library
+    let report = validate_font_file(source);
+    if report.has_errors() {
+        let reasons: Vec<&str> = report.errors().map(|i| i.message.as_str()).collect();
+        return Err(FontError::new(
+            FontErrorKind::InvalidFont,
+            Some(source),
+            format!("Font validation failed for {}: {}", source.display(), reasons.join("; ")),
+        ));
+    }
+
+    let hash = calculate_file_hash(source).map_err(|e| FontError::io(source, "Failed to read", &e))?;
+
+    let _guard = CATALOG_LOCK.lock().unwrap();
+    let mut catalog = load(app)?;
+    if let Some(entry) = catalog.fonts.iter().find(|e| e.file.hash == hash) {
+        return Ok(entry.clone());
+    }
+
+    let dest = library_dir(app)?.join(format!("{}.{}", hash, ext));
+    if !dest.exists() {
+        fs::copy(source, &dest).map_err(|e| FontError::io(&dest, "Failed to copy file to", &e))?;
+    }
+
+    // Described from the library copy, so face ids and paths point there
+    let faces = match describe_file(&dest) {
+        Ok(faces) => faces,
+        Err(error) => {
+            let _ = fs::remove_file(&dest);
+            return Err(FontError {
+                path: Some(source.to_string_lossy().to_string()),
+                ..error
+            });
+        }
+    };
+    let entry = CatalogEntry {
+        file: LibraryFile {
+            hash,
+            path: dest.to_string_lossy().to_string(),
+            file_name: source.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            family: faces[0].family.clone(),
+            postscript_names: faces.iter().filter_map(|f| f.postscript_name.clone()).collect(),
+        },
+        source_path: source.to_string_lossy().to_string(),
+        size: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+        imported_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        faces,
+    };
+
+    catalog.fonts.push(entry.clone());
+    save(app, &catalog)?;
+    Ok(entry)
+}
+
+// Library faces for list_fonts. Installed variants whose file is byte-identical to a library
+// file are tagged with its hash instead of being listed twice.
+pub fn list_library_variants(app: &AppHandle, installed: &mut [FontVariant]) -> Result<Vec<FontVariant>, FontError> {
+    let catalog = {
+        let _guard = CATALOG_LOCK.lock().unwrap();
+        load(app)?
+    };
+
+    let mut by_postscript: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, variant) in installed.iter().enumerate() {
+        if let Some(name) = &variant.postscript_name {
+            by_postscript.entry(name.clone()).or_default().push(i);
+        }
+    }
+    let mut hashes: HashMap<String, Option<String>> = HashMap::new();
+
+    let mut variants = Vec::new();
+    for entry in catalog.fonts {
+        for face in entry.faces {
+            let twin = face.postscript_name.as_ref().and_then(|name| by_postscript.get(name)).and_then(|candidates| {
+                candidates.iter().copied().find(|&i| {
+                    let path = &installed[i].path;
+                    let same_size = fs::metadata(path).map(|m| m.len() == entry.size).unwrap_or(false);
+                    same_size
+                        && hashes
+                            .entry(path.clone())
+                            .or_insert_with(|| calculate_file_hash(Path::new(path)).ok())
+                            .as_deref()
+                            == Some(entry.file.hash.as_str())
+                })
+            });
+
+            // Activated files are installed variants too, so anything without a twin is library-only
+            match twin {
+                Some(i) => installed[i].library_hash = Some(entry.file.hash.clone()),
+                None => variants.push(FontVariant {
+                    installed: false,
+                    scope: FontScope::Library,
+                    registry_value_name: None,
+                    library_hash: Some(entry.file.hash.clone()),
+                    ..face
+                }),
+            }
+        }
+    }
+    Ok(variants)
+}
+
+fn describe_file(path: &Path) -> Result<Vec<FontVariant>, FontError> {
+    let mut db = Database::new();
+    db.load_font_file(path).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(path),
+            format!("Failed to parse font file {}: {}", path.display(), e),
+        )
+    })?;
+    let faces: Vec<FontVariant> = db.faces().map(describe_face).collect();
+    if faces.is_empty() {
+        return Err(FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(path),
+            format!("No font faces found in file: {}", path.display()),
+        ));
+    }
+    Ok(faces)
+}
+
+fn catalog_file(app: &AppHandle) -> Result<PathBuf, FontError> {
+    Ok(library_dir(app)?.join(CATALOG_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<LibraryCatalog, FontError> {
+    read_json(&catalog_file(app)?)
+}
+
+fn save(app: &AppHandle, catalog: &LibraryCatalog) -> Result<(), FontError> {
+    write_json(&catalog_file(app)?, catalog)
+}
@@ -0,0 +1,153 @@
+use crate::fonts::enumerate::installed_variants;
+use crate::fonts::FontVariant;
+use serde::{Deserialize, Serialize};
+use ttf_parser::os2::Permissions;
+use ttf_parser::{Face, Tag};
+
+// OS/2 fsType embedding levels, least permissive first
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum EmbeddingLevel {
+    // Must not be embedded at all
+    Restricted,
+    // Embedded documents may be viewed and printed, not edited
+    PreviewPrint,
+    // Embedded documents may be edited
+    Editable,
+    // May be embedded and installed on the receiving system
+    Installable,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LicenceInfo {
+    // Raw OS/2 fsType
+    pub fs_type: u16,
+    // None without an OS/2 table, or when a version 3+ table sets conflicting bits
+    pub embedding: Option<EmbeddingLevel>,
+    pub no_subsetting: bool,
+    // Only bitmaps may be embedded, no outlines
+    pub bitmap_only: bool,
+    // Name ID 13
+    pub description: Option<String>,
+    // Name ID 14
+    pub url: Option<String>,
+}
+
+impl LicenceInfo {
+    // Outlines may go into a PDF: anything but restricted, and not bitmap-only
+    pub fn pdf_embeddable(&self) -> bool {
+        matches!(
+            self.embedding,
+            Some(EmbeddingLevel::PreviewPrint | EmbeddingLevel::Editable | EmbeddingLevel::Installable)
+        ) && !self.bitmap_only
+    }
+
+    // The file may ship inside an app and be installed there; fsType alone, the licence text may say more
+    pub fn app_embeddable(&self) -> bool {
+        self.embedding == Some(EmbeddingLevel::Installable) && !self.bitmap_only
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenceReportEntry {
+    pub id: String,
+    pub family: String,
+    pub full_name: Option<String>,
+    pub path: String,
+    pub licence: LicenceInfo,
+    pub pdf_embeddable: bool,
+    pub app_embeddable: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenceReport {
+    pub total: usize,
+    pub pdf_embeddable: usize,
+    pub app_embeddable: usize,
+    // fsType forbids embedding outright
+    pub restricted: usize,
+    // Embeddable only as bitmaps, so not usable for PDFs with outlines
+    pub bitmap_only: usize,
+    // No OS/2 table, or conflicting fsType bits
+    pub unknown: usize,
+    // Neither a licence description nor a URL in the name table
+    pub without_licence_text: usize,
+    // Most restrictive first
+    pub fonts: Vec<LicenceReportEntry>,
+}
+
+pub fn read_licence(face: &Face) -> LicenceInfo {
+    let os2 = face.tables().os2;
+    let name = |id: u16| {
+        face.names()
+            .into_iter()
+            .filter(|n| n.name_id == id && n.is_unicode())
+            .find_map(|n| n.to_string())
+            .filter(|s| !s.trim().is_empty())
+    };
+
+    LicenceInfo {
+        fs_type: face
+            .raw_face()
+            .table(Tag::from_bytes(b"OS/2"))
+            .and_then(|t| t.get(8..10))
+            .map_or(0, |b| u16::from_be_bytes([b[0], b[1]])),
+        embedding: os2.and_then(|t| t.permissions()).map(|p| match p {
+            Permissions::Installable => EmbeddingLevel::Installable,
+            Permissions::Restricted => EmbeddingLevel::Restricted,
+            Permissions::PreviewAndPrint => EmbeddingLevel::PreviewPrint,
+            Permissions::Editable => EmbeddingLevel::Editable,
+        }),
+        no_subsetting: os2.is_some_and(|t| !t.is_subsetting_allowed()),
+        bitmap_only: os2.is_some_and(|t| !t.is_outline_embedding_allowed()),
+        description: name(13),
+        url: name(14),
+    }
+}
+
+// What every installed face allows, for deciding what may go into shipped PDFs and apps
+#[tauri::command]
+pub async fn licence_report() -> LicenceReport {
+    let mut fonts: Vec<LicenceReportEntry> = installed_variants().into_iter().map(report_entry).collect();
+    fonts.sort_by(|a, b| {
+        a.licence
+            .embedding
+            .cmp(&b.licence.embedding)
+            .then_with(|| a.family.cmp(&b.family))
+    });
+
+    LicenceReport {
+        total: fonts.len(),
+        pdf_embeddable: fonts.iter().filter(|f| f.pdf_embeddable).count(),
+        app_embeddable: fonts.iter().filter(|f| f.app_embeddable).count(),
+        restricted: fonts
+            .iter()
+            .filter(|f| f.licence.embedding == Some(EmbeddingLevel::Restricted))
+            .count(),
+        bitmap_only: fonts
+            .iter()
+            .filter(|f| f.licence.bitmap_only && f.licence.embedding > Some(EmbeddingLevel::Restricted))
+            .count(),
+        unknown: fonts.iter().filter(|f| f.licence.embedding.is_none()).count(),
+        without_licence_text: fonts
+            .iter()
+            .filter(|f| f.licence.description.is_none() && f.licence.url.is_none())
+            .count(),
+        fonts,
+    }
+}
+
+fn report_entry(v: FontVariant) -> LicenceReportEntry {
+    LicenceReportEntry {
+        pdf_embeddable: v.licence.pdf_embeddable(),
+        app_embeddable: v.licence.app_embeddable(),
+        id: v.id,
+        family: v.family,
+        full_name: v.full_name,
+        path: v.path,
+        licence: v.licence,
+    }
+}
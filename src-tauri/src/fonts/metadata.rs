@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use ttf_parser::{Face, Tag};
+
+// Scripts and characters a face needs to map to count as covering them
+const SCRIPT_SAMPLES: &[(&str, &str)] = &[
+    ("latin", "AZaz"),
+    ("greek", "ΑΩαω"),
+    ("cyrillic", "АЯая"),
+    ("arabic", "ابيه"),
+    ("hebrew", "אשת"),
+    ("devanagari", "कहि"),
+    ("thai", "กฮ"),
+    ("han", "中国人的"),
+    ("kana", "あんアン"),
+    ("hangul", "한가"),
+];
+
+// Searchable details read from the face's tables
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FaceMetadata {
+    // Family and full names in every language the name table carries
+    pub localized_names: Vec<String>,
+    // OS/2 usWidthClass: 1 ultra-condensed, 5 normal, 9 ultra-expanded
+    pub width: u16,
+    pub monospace: bool,
+    pub variable: bool,
+    // Has COLR, SVG, sbix or CBDT glyphs
+    pub colour: bool,
+    // OS/2 achVendID with the padding trimmed, e.g. "MS"
+    pub vendor: Option<String>,
+    // Name ID 5
+    pub version: Option<String>,
+    // Keys of SCRIPT_SAMPLES the cmap covers
+    pub scripts: Vec<String>,
+    // GSUB and GPOS feature tags, e.g. "liga", "smcp"
+    pub features: Vec<String>,
+}
+
+pub fn read_face_metadata(face: &Face) -> FaceMetadata {
+    let mut names = Vec::new();
+    for name in face.names() {
+        if matches!(name.name_id, 1 | 4 | 16) && name.is_unicode() {
+            if let Some(s) = name.to_string() {
+                if !s.is_empty() && !names.contains(&s) {
+                    names.push(s);
+                }
+            }
+        }
+    }
+
+    let version = face
+        .names()
+        .into_iter()
+        .filter(|n| n.name_id == 5 && n.is_unicode())
+        .find_map(|n| n.to_string());
+
+    let vendor = face
+        .raw_face()
+        .table(Tag::from_bytes(b"OS/2"))
+        .and_then(|os2| os2.get(58..62))
+        .map(|id| String::from_utf8_lossy(id).trim_matches(|c: char| c == ' ' || c == '\0').to_string())
+        .filter(|id| !id.is_empty());
+
+    let tables = face.tables();
+    let colour = tables.colr.is_some() || tables.svg.is_some() || tables.sbix.is_some() || tables.cbdt.is_some();
+
+    let scripts = SCRIPT_SAMPLES
+        .iter()
+        .filter(|(_, sample)| sample.chars().all(|c| face.glyph_index(c).is_some()))
+        .map(|(script, _)| script.to_string())
+        .collect();
+
+    let mut features = BTreeSet::new();
+    for table in [tables.gsub, tables.gpos].into_iter().flatten() {
+        for feature in table.features {
+            features.insert(feature.tag.to_string());
+        }
+    }
+
+    FaceMetadata {
+        localized_names: names,
+        width: face.width().to_number(),
+        monospace: face.is_monospaced(),
+        variable: face.is_variable(),
+        colour,
+        vendor,
+        version,
+        scripts,
+        features: features.into_iter().collect(),
+    }
+}
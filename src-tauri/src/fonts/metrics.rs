@@ -0,0 +1,326 @@
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::preview::allowed_font_path;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use ttf_parser::Face;
+
+// Two line heights further apart than this fraction of the em are visibly different
+const LINE_HEIGHT_TOLERANCE: f32 = 0.02;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct VerticalMetrics {
+    pub ascender: i16,
+    // Negative below the baseline
+    pub descender: i16,
+    pub line_gap: i16,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct LineDecoration {
+    pub position: i16,
+    pub thickness: i16,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MetricsIssueKind {
+    // Glyphs reach beyond usWinAscent/usWinDescent and get cut off by GDI
+    Clipping,
+    // Windows and macOS space lines differently
+    LineHeightMismatch,
+    // hhea and the OS/2 typo metrics describe different lines and USE_TYPO_METRICS doesn't pick one
+    TypoHheaMismatch,
+    // OS/2 older than version 2, or the value is zero
+    MissingHeight,
+    // Underline or strikeout that would be invisible or drawn in the wrong place
+    Decoration,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsIssue {
+    pub kind: MetricsIssueKind,
+    pub message: String,
+}
+
+// Everything in font units unless noted
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FontMetrics {
+    pub path: String,
+    pub face_index: u32,
+    pub units_per_em: u16,
+    pub hhea: VerticalMetrics,
+    // None without an OS/2 table
+    pub typo: Option<VerticalMetrics>,
+    // usWinAscent and usWinDescent, both positive as stored
+    pub win_ascent: Option<u16>,
+    pub win_descent: Option<u16>,
+    // OS/2 fsSelection bit 7: Windows uses the typo metrics instead of the win ones
+    pub use_typo_metrics: bool,
+    pub x_height: Option<i16>,
+    pub cap_height: Option<i16>,
+    pub underline: Option<LineDecoration>,
+    pub strikeout: Option<LineDecoration>,
+    // head's bounding box of all glyphs
+    pub y_min: i16,
+    pub y_max: i16,
+    // Default line spacing in ems: Windows (GDI) and macOS (Core Text)
+    pub windows_line_height: f32,
+    pub mac_line_height: f32,
+    pub issues: Vec<MetricsIssue>,
+}
+
+#[tauri::command]
+pub fn get_font_metrics(path: String, face_index: Option<u32>) -> Result<FontMetrics, FontError> {
+    let p = Path::new(&path);
+    let canonical = allowed_font_path(p)?;
+    let data = fs::read(&canonical).map_err(|e| FontError::io(p, "Failed to read font file", &e))?;
+    let index = face_index.unwrap_or(0);
+    let face = Face::parse(&data, index).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(p),
+            format!("Failed to parse face {} of {}: {}", index, p.display(), e),
+        )
+    })?;
+    Ok(read_metrics(path, index, &face))
+}
+
+fn read_metrics(path: String, face_index: u32, face: &Face) -> FontMetrics {
+    let upem = face.units_per_em();
+    let hhea_table = face.tables().hhea;
+    let hhea = VerticalMetrics {
+        ascender: hhea_table.ascender,
+        descender: hhea_table.descender,
+        line_gap: hhea_table.line_gap,
+    };
+    let os2 = face.tables().os2;
+    let typo = os2.map(|t| VerticalMetrics {
+        ascender: t.typographic_ascender(),
+        descender: t.typographic_descender(),
+        line_gap: t.typographic_line_gap(),
+    });
+    let win_ascent = os2.map(|t| t.windows_ascender() as u16);
+    let win_descent = os2.map(|t| t.windows_descender().unsigned_abs());
+    let use_typo_metrics = os2.is_some_and(|t| t.use_typographic_metrics());
+    let bbox = face.global_bounding_box();
+
+    let em = |units: i32| units as f32 / upem as f32;
+    let total = |m: &VerticalMetrics| m.ascender as i32 - m.descender as i32 + m.line_gap as i32;
+    // GDI spaces lines by usWinAscent + usWinDescent unless the font opts into the typo metrics
+    let windows_line_height = match (typo, win_ascent, win_descent) {
+        (Some(typo), _, _) if use_typo_metrics => em(total(&typo)),
+        (_, Some(ascent), Some(descent)) => em(ascent as i32 + descent as i32),
+        _ => em(total(&hhea)),
+    };
+    let mac_line_height = em(total(&hhea));
+
+    let mut metrics = FontMetrics {
+        path,
+        face_index,
+        units_per_em: upem,
+        hhea,
+        typo,
+        win_ascent,
+        win_descent,
+        use_typo_metrics,
+        x_height: face.x_height(),
+        cap_height: face.capital_height(),
+        underline: face.underline_metrics().map(|m| LineDecoration {
+            position: m.position,
+            thickness: m.thickness,
+        }),
+        strikeout: face.strikeout_metrics().map(|m| LineDecoration {
+            position: m.position,
+            thickness: m.thickness,
+        }),
+        y_min: bbox.y_min,
+        y_max: bbox.y_max,
+        windows_line_height,
+        mac_line_height,
+        issues: Vec::new(),
+    };
+    metrics.issues = find_issues(&metrics);
+    metrics
+}
+
+fn find_issues(m: &FontMetrics) -> Vec<MetricsIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |kind, message: String| issues.push(MetricsIssue { kind, message });
+
+    if let (Some(ascent), Some(descent)) = (m.win_ascent, m.win_descent) {
+        if (ascent as i32) < m.y_max as i32 {
+            issue(
+                MetricsIssueKind::Clipping,
+                format!(
+                    "usWinAscent {} is below the tallest glyph ({}); tops get clipped on Windows",
+                    ascent, m.y_max
+                ),
+            );
+        }
+        if (descent as i32) < -(m.y_min as i32) {
+            issue(
+                MetricsIssueKind::Clipping,
+                format!(
+                    "usWinDescent {} is above the lowest glyph ({}); descenders get clipped on Windows",
+                    descent, m.y_min
+                ),
+            );
+        }
+    }
+
+    if (m.windows_line_height - m.mac_line_height).abs() > LINE_HEIGHT_TOLERANCE {
+        issue(
+            MetricsIssueKind::LineHeightMismatch,
+            format!(
+                "Line height is {:.3} em on Windows but {:.3} em on macOS",
+                m.windows_line_height, m.mac_line_height
+            ),
+        );
+    }
+
+    if let Some(typo) = m.typo {
+        let same =
+            typo.ascender == m.hhea.ascender && typo.descender == m.hhea.descender && typo.line_gap == m.hhea.line_gap;
+        if !same && !m.use_typo_metrics {
+            issue(
+                MetricsIssueKind::TypoHheaMismatch,
+                format!(
+                    "Typo metrics ({} / {} / {}) differ from hhea ({} / {} / {}) and USE_TYPO_METRICS is off; \
+                     apps that read the typo values lay text out differently",
+                    typo.ascender, typo.descender, typo.line_gap, m.hhea.ascender, m.hhea.descender, m.hhea.line_gap
+                ),
+            );
+        }
+    }
+
+    for (name, value) in [("x-height", m.x_height), ("Cap height", m.cap_height)] {
+        if value.is_none_or(|v| v <= 0) {
+            issue(MetricsIssueKind::MissingHeight, format!("{} is not set in OS/2", name));
+        }
+    }
+
+    for (name, line) in [("Underline", m.underline), ("Strikeout", m.strikeout)] {
+        match line {
+            None => issue(MetricsIssueKind::Decoration, format!("{} metrics are missing", name)),
+            Some(line) if line.thickness <= 0 => issue(
+                MetricsIssueKind::Decoration,
+                format!("{} thickness is {}", name, line.thickness),
+            ),
+            _ => {}
+        }
+    }
+    if let Some(underline) = m.underline.filter(|u| u.position > 0) {
+        issue(
+            MetricsIssueKind::Decoration,
+            format!("Underline position {} is above the baseline", underline.position),
+        );
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A face with nothing to report: win and hhea metrics cover the glyphs and agree
+    fn healthy() -> FontMetrics {
+        let lines = VerticalMetrics {
+            ascender: 900,
+            descender: -250,
+            line_gap: 0,
+        };
+        FontMetrics {
+            path: String::new(),
+            face_index: 0,
+            units_per_em: 1000,
+            hhea: lines,
+            typo: Some(lines),
+            win_ascent: Some(900),
+            win_descent: Some(250),
+            use_typo_metrics: false,
+            x_height: Some(500),
+            cap_height: Some(700),
+            underline: Some(LineDecoration {
+                position: -100,
+                thickness: 50,
+            }),
+            strikeout: Some(LineDecoration {
+                position: 300,
+                thickness: 50,
+            }),
+            y_min: -220,
+            y_max: 880,
+            windows_line_height: 1.15,
+            mac_line_height: 1.15,
+            issues: Vec::new(),
+        }
+    }
+
+    fn kinds(m: &FontMetrics) -> Vec<MetricsIssueKind> {
+        find_issues(m).into_iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn healthy_face_has_no_issues() {
+        assert!(find_issues(&healthy()).is_empty());
+    }
+
+    #[test]
+    fn glyphs_beyond_the_win_metrics_clip() {
+        let mut m = healthy();
+        m.y_max = 950;
+        m.y_min = -300;
+        assert_eq!(kinds(&m), [MetricsIssueKind::Clipping, MetricsIssueKind::Clipping]);
+    }
+
+    #[test]
+    fn line_heights_beyond_the_tolerance_mismatch() {
+        let mut m = healthy();
+        m.mac_line_height = 1.16;
+        assert!(kinds(&m).is_empty());
+        m.mac_line_height = 1.2;
+        assert_eq!(kinds(&m), [MetricsIssueKind::LineHeightMismatch]);
+    }
+
+    #[test]
+    fn typo_metrics_only_matter_without_use_typo_metrics() {
+        let mut m = healthy();
+        m.typo = Some(VerticalMetrics {
+            ascender: 750,
+            descender: -250,
+            line_gap: 150,
+        });
+        assert_eq!(kinds(&m), [MetricsIssueKind::TypoHheaMismatch]);
+        m.use_typo_metrics = true;
+        assert!(kinds(&m).is_empty());
+    }
+
+    #[test]
+    fn missing_or_zero_heights_are_reported() {
+        let mut m = healthy();
+        m.x_height = None;
+        m.cap_height = Some(0);
+        assert_eq!(kinds(&m), [MetricsIssueKind::MissingHeight, MetricsIssueKind::MissingHeight]);
+    }
+
+    #[test]
+    fn bad_decorations_are_reported() {
+        let mut m = healthy();
+        m.strikeout = None;
+        assert_eq!(kinds(&m), [MetricsIssueKind::Decoration]);
+
+        let mut m = healthy();
+        m.underline = Some(LineDecoration {
+            position: 50,
+            thickness: 0,
+        });
+        // Zero thickness and above the baseline are separate problems
+        assert_eq!(kinds(&m), [MetricsIssueKind::Decoration, MetricsIssueKind::Decoration]);
+    }
+}
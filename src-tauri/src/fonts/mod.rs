@@ -1,4 +1,9 @@
+use crate::fonts::classify::FontClass;
+use crate::fonts::identity::NameVersionKey;
+use crate::fonts::licence::LicenceInfo;
+use crate::fonts::metadata::FaceMetadata;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -8,9 +13,48 @@ pub struct FontVariant {
     pub style: String,
     pub weight: u16,
     pub path: String,
+    // Index of the face within a collection file; 0 for single-face files
+    #[serde(default)]
+    pub face_index: u32,
     pub postscript_name: Option<String>,
     pub full_name: Option<String>,
+    // Lives in %SystemRoot%\Fonts, so a per-user uninstall can't touch it
     pub is_system_core: bool,
+    pub scope: FontScope,
+    // Name of the HKLM or HKCU Fonts value that registers the file, e.g. "Arial (TrueType)"
+    pub registry_value_name: Option<String>,
+    // Windows can use it; false for fonts that only sit in the app library
+    pub installed: bool,
+    // Set when the same file is kept in the app library
+    pub library_hash: Option<String>,
+    // Derived from the face's content (see identity::face_identity), unlike the path-based id.
+    // Survives moves and reinstalls; tags, collections and favourites are keyed by it.
+    #[serde(default)]
+    pub identity: String,
+    // Secondary key: the same PostScript name and version is the same font even when its tables
+    // differ, e.g. after a re-subset. None when the name table can't be read.
+    #[serde(default)]
+    pub name_version: Option<NameVersionKey>,
+    // Broad design class from OS/2 sFamilyClass and PANOSE
+    pub classification: FontClass,
+    pub metadata: FaceMetadata,
+    // OS/2 fsType embedding permissions and the licence names from the name table
+    pub licence: LicenceInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FontScope {
+    // Shipped with Windows
+    SystemCore,
+    // Registered in HKLM by an administrator or deployment tool
+    MachineInstalled,
+    // Registered in HKCU
+    PerUser,
+    // Loaded by Windows but not registered in either hive
+    Unregistered,
+    // Only in the app library
+    Library,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,7 +71,54 @@ pub struct ListFontsResult {
     pub total_variants: usize,
 }
 
+// %SystemRoot%\Fonts, where Windows and machine-wide installs put fonts
+pub fn system_fonts_dir() -> PathBuf {
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+    PathBuf::from(system_root).join("Fonts")
+}
+
+// %LOCALAPPDATA%\Microsoft\Windows\Fonts, where per-user installs put fonts
+pub fn user_fonts_dir() -> PathBuf {
+    let local_app_data = std::env::var("LOCALAPPDATA").unwrap_or_default();
+    PathBuf::from(local_app_data)
+        .join("Microsoft")
+        .join("Windows")
+        .join("Fonts")
+}
+
+// Registry font values hold either an absolute path or a file name relative to %SystemRoot%\Fonts
+pub fn resolve_registry_path(data: &str) -> PathBuf {
+    let path = Path::new(data);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        system_fonts_dir().join(path)
+    }
+}
+
+pub mod activation;
+pub mod backup;
+pub mod classify;
+pub mod duplicates;
+pub mod elevation;
 pub mod enumerate;
+pub mod error;
+pub mod identity;
 pub mod install;
+pub mod inventory;
+pub mod library;
+pub mod licence;
+pub mod metadata;
+pub mod metrics;
+pub mod organize;
+pub mod pending;
+pub mod persist;
+pub mod plan;
 pub mod preview;
+pub mod quarantine;
+pub mod repair;
+pub mod search;
+pub mod similar;
 pub mod uninstall;
+pub mod validate;
+pub mod version;
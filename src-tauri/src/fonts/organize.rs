@@ -0,0 +1,292 @@
+use crate::fonts::enumerate::cached_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::identity::NameVersionKey;
+use crate::fonts::persist::{app_data_dir, read_json, write_json};
+use crate::fonts::FontVariant;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const ORGANIZER_FILE: &str = "organizer.json";
+
+// Serialises read-modify-write cycles on the organizer file
+static ORGANIZER_LOCK: Mutex<()> = Mutex::new(());
+
+// Everything here is keyed by FontVariant::identity, so it follows a font across moves and reinstalls
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Organizer {
+    // Font identity -> its tags
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+    pub collections: Vec<Collection>,
+    pub favourites: BTreeSet<String>,
+    // PostScript name and version of each identity used above, so a font whose tables changed,
+    // e.g. after a re-subset, is still found through key_for
+    pub name_versions: BTreeMap<String, NameVersionKey>,
+}
+
+impl Organizer {
+    fn refers_to(&self, identity: &str) -> bool {
+        self.tags.contains_key(identity)
+            || self.favourites.contains(identity)
+            || self.collections.iter().any(|c| c.fonts.iter().any(|f| f == identity))
+    }
+
+    // The identity the font is kept under here: its own, or failing that one recorded with the
+    // same PostScript name and version
+    pub fn key_for<'a>(&'a self, variant: &'a FontVariant) -> &'a str {
+        if self.refers_to(&variant.identity) {
+            return &variant.identity;
+        }
+        variant
+            .name_version
+            .as_ref()
+            .and_then(|key| self.name_versions.iter().find(|(_, k)| *k == key))
+            .map(|(identity, _)| identity.as_str())
+            .unwrap_or(&variant.identity)
+    }
+
+    pub fn tags_for(&self, variant: &FontVariant) -> Option<&BTreeSet<String>> {
+        self.tags.get(self.key_for(variant))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub name: String,
+    // Font identities, in the order they were added
+    pub fonts: Vec<String>,
+    // Unix timestamp in seconds
+    pub created_at: u64,
+}
+
+// Narrows list_fonts; every set criterion has to match
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FontFilter {
+    pub tag: Option<String>,
+    pub collection: Option<String>,
+    pub favourites_only: bool,
+}
+
+#[tauri::command]
+pub fn get_organizer(app: AppHandle) -> Result<Organizer, FontError> {
+    let _guard = ORGANIZER_LOCK.lock().unwrap();
+    load(&app)
+}
+
+// Replaces the font's tags; an empty list removes them all
+#[tauri::command]
+pub fn set_font_tags(app: AppHandle, identity: String, tags: Vec<String>) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        let tags: BTreeSet<String> = tags.iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+        if tags.is_empty() {
+            org.tags.remove(&identity);
+        } else {
+            org.tags.insert(identity, tags);
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn rename_tag(app: AppHandle, from: String, to: String) -> Result<Organizer, FontError> {
+    // Trimmed like the tags set_font_tags stores
+    let to = to.trim().to_string();
+    if to.is_empty() {
+        return Err(FontError::new(FontErrorKind::InvalidName, None, "Tag names can't be empty"));
+    }
+    update(&app, |org| {
+        for tags in org.tags.values_mut() {
+            if tags.remove(&from) {
+                tags.insert(to.clone());
+            }
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_tag(app: AppHandle, tag: String) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        for tags in org.tags.values_mut() {
+            tags.remove(&tag);
+        }
+        org.tags.retain(|_, tags| !tags.is_empty());
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn set_favourite(app: AppHandle, identity: String, favourite: bool) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        if favourite {
+            org.favourites.insert(identity);
+        } else {
+            org.favourites.remove(&identity);
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn create_collection(app: AppHandle, name: String) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        if org.collections.iter().any(|c| c.name == name) {
+            return Err(FontError::new(
+                FontErrorKind::AlreadyExists,
+                None,
+                format!("A collection named {} already exists", name),
+            ));
+        }
+        org.collections.push(Collection {
+            name,
+            fonts: Vec::new(),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        });
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn rename_collection(app: AppHandle, from: String, to: String) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        if from != to && org.collections.iter().any(|c| c.name == to) {
+            return Err(FontError::new(
+                FontErrorKind::AlreadyExists,
+                None,
+                format!("A collection named {} already exists", to),
+            ));
+        }
+        find_collection(org, &from)?.name = to;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_collection(app: AppHandle, name: String) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        find_collection(org, &name)?;
+        org.collections.retain(|c| c.name != name);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn add_to_collection(app: AppHandle, name: String, identities: Vec<String>) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        let collection = find_collection(org, &name)?;
+        for identity in identities {
+            if !collection.fonts.contains(&identity) {
+                collection.fonts.push(identity);
+            }
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_from_collection(app: AppHandle, name: String, identities: Vec<String>) -> Result<Organizer, FontError> {
+    update(&app, |org| {
+        find_collection(org, &name)?.fonts.retain(|f| !identities.contains(f));
+        Ok(())
+    })
+}
+
+// Keeps the variants matching the filter. An unreadable store filters everything out rather than nothing.
+pub fn filter_variants(app: &AppHandle, filter: &FontFilter, variants: Vec<FontVariant>) -> Vec<FontVariant> {
+    let org = {
+        let _guard = ORGANIZER_LOCK.lock().unwrap();
+        load(app).unwrap_or_default()
+    };
+    let collection = filter
+        .collection
+        .as_ref()
+        .map(|name| org.collections.iter().find(|c| &c.name == name).map(|c| &c.fonts));
+
+    variants
+        .into_iter()
+        .filter(|v| {
+            let key = org.key_for(v);
+            let tagged = match &filter.tag {
+                Some(tag) => org.tags.get(key).is_some_and(|tags| tags.contains(tag)),
+                None => true,
+            };
+            let collected = match collection {
+                Some(Some(fonts)) => fonts.iter().any(|f| f == key),
+                Some(None) => false,
+                None => true,
+            };
+            tagged && collected && (!filter.favourites_only || org.favourites.contains(key))
+        })
+        .collect()
+}
+
+// The organizer for lookups outside this module; an unreadable store is empty
+pub fn organizer(app: &AppHandle) -> Organizer {
+    let _guard = ORGANIZER_LOCK.lock().unwrap();
+    load(app).unwrap_or_default()
+}
+
+// Adds tags to the given fonts, keeping the ones they already have
+pub fn merge_tags(app: &AppHandle, tags: BTreeMap<String, BTreeSet<String>>) -> Result<Organizer, FontError> {
+    update(app, |org| {
+        for (identity, tags) in tags {
+            org.tags.entry(identity).or_default().extend(tags);
+        }
+        Ok(())
+    })
+}
+
+fn find_collection<'a>(org: &'a mut Organizer, name: &str) -> Result<&'a mut Collection, FontError> {
+    org.collections.iter_mut().find(|c| c.name == name).ok_or_else(|| {
+        FontError::new(
+            FontErrorKind::NotFound,
+            None,
+            format!("No collection named {}", name),
+        )
+    })
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut Organizer) -> Result<(), FontError>) -> Result<Organizer, FontError> {
+    let _guard = ORGANIZER_LOCK.lock().unwrap();
+    let mut org = load(app)?;
+    change(&mut org)?;
+    record_name_versions(app, &mut org);
+    write_json(&organizer_file(app)?, &org)?;
+    Ok(org)
+}
+
+// Keeps name_versions to the identities in use, adding the ones the current fonts can tell
+fn record_name_versions(app: &AppHandle, org: &mut Organizer) {
+    let in_use: BTreeSet<String> = org
+        .tags
+        .keys()
+        .chain(&org.favourites)
+        .chain(org.collections.iter().flat_map(|c| &c.fonts))
+        .cloned()
+        .collect();
+    org.name_versions.retain(|identity, _| in_use.contains(identity));
+    if in_use.iter().all(|identity| org.name_versions.contains_key(identity)) {
+        return;
+    }
+    for variant in cached_variants(app, true) {
+        if let Some(key) = variant.name_version {
+            if in_use.contains(&variant.identity) {
+                org.name_versions.entry(variant.identity).or_insert(key);
+            }
+        }
+    }
+}
+
+fn organizer_file(app: &AppHandle) -> Result<PathBuf, FontError> {
+    Ok(app_data_dir(app)?.join(ORGANIZER_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<Organizer, FontError> {
+    read_json(&organizer_file(app)?)
+}
@@ -0,0 +1,105 @@
+use crate::fonts::error::FontError;
+use crate::fonts::install::calculate_file_hash;
+use crate::fonts::persist::{app_data_dir, read_json, write_json};
+use crate::win::delete_on_reboot;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const PENDING_FILE: &str = "pending_deletions.json";
+
+// Serialises read-modify-write cycles on the pending list
+static PENDING_LOCK: Mutex<()> = Mutex::new(());
+
+// A font file that was unregistered but couldn't be deleted because an application still had it open
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingDeletion {
+    // Where the leftover lives now, after being renamed aside
+    pub path: String,
+    pub original_path: String,
+    // SHA-256 of the file when it was scheduled; a file that no longer matches is left alone
+    pub hash: String,
+    // Unix timestamp in seconds
+    pub scheduled_at: u64,
+    // Windows will also delete it during the next boot
+    pub reboot_scheduled: bool,
+}
+
+#[tauri::command]
+pub fn list_pending_deletions(app: AppHandle) -> Result<Vec<PendingDeletion>, FontError> {
+    let _guard = PENDING_LOCK.lock().unwrap();
+    read_json(&pending_file(&app)?)
+}
+
+// Tries the pending deletions again, e.g. after the user closed the applications, and returns what is left
+#[tauri::command]
+pub fn retry_pending_deletions(app: AppHandle) -> Result<Vec<PendingDeletion>, FontError> {
+    process_pending_deletions(&app)
+}
+
+// Renames the in-use file aside so the original name is free again, and remembers it for deletion.
+// Fails when the file can't be renamed: a later install may reuse the original name, so it is never scheduled.
+pub fn schedule_deletion(app: &AppHandle, font_path: &Path) -> Result<PendingDeletion, FontError> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let file_name = font_path.file_name().unwrap_or_default().to_string_lossy();
+    let aside = font_path.with_file_name(format!("{}.{:x}.delete", file_name, now.as_nanos()));
+
+    let hash = calculate_file_hash(font_path).map_err(|e| FontError::io(font_path, "Failed to read", &e))?;
+    // Renaming usually works even while GDI has the file mapped
+    fs::rename(font_path, &aside).map_err(|e| FontError::io(font_path, "Failed to move aside", &e))?;
+
+    let pending = PendingDeletion {
+        path: aside.to_string_lossy().to_string(),
+        original_path: font_path.to_string_lossy().to_string(),
+        hash,
+        scheduled_at: now.as_secs(),
+        reboot_scheduled: delete_on_reboot(&aside),
+    };
+
+    let _guard = PENDING_LOCK.lock().unwrap();
+    let file = pending_file(app)?;
+    let mut list: Vec<PendingDeletion> = read_json(&file)?;
+    list.push(pending.clone());
+    write_json(&file, &list)?;
+    Ok(pending)
+}
+
+// Runs at startup: deletes whatever has been released since, keeps the rest for next time
+pub fn process_pending_deletions(app: &AppHandle) -> Result<Vec<PendingDeletion>, FontError> {
+    let _guard = PENDING_LOCK.lock().unwrap();
+    let file = pending_file(app)?;
+    let list: Vec<PendingDeletion> = read_json(&file)?;
+    if list.is_empty() {
+        return Ok(list);
+    }
+
+    let remaining: Vec<PendingDeletion> = list.into_iter().filter(|p| !try_delete(p)).collect();
+
+    write_json(&file, &remaining)?;
+    Ok(remaining)
+}
+
+// True once the entry is settled: deleted, already gone, or no longer the file that was scheduled
+fn try_delete(pending: &PendingDeletion) -> bool {
+    let path = Path::new(&pending.path);
+    let unchanged = match calculate_file_hash(path) {
+        Ok(current) => current == pending.hash,
+        Err(e) => return e.kind() == io::ErrorKind::NotFound,
+    };
+    if !unchanged {
+        return true;
+    }
+    match fs::remove_file(path) {
+        Ok(()) => true,
+        Err(e) => e.kind() == io::ErrorKind::NotFound,
+    }
+}
+
+fn pending_file(app: &AppHandle) -> Result<PathBuf, FontError> {
+    Ok(app_data_dir(app)?.join(PENDING_FILE))
+}
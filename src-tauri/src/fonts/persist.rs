@@ -0,0 +1,65 @@
+use crate::fonts::error::{FontError, FontErrorKind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+// Per-user directory for everything the app keeps between runs (quarantine, lists, catalogs)
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, FontError> {
+    let dir = app.path().app_local_data_dir().map_err(|e| {
+        FontError::new(
+            FontErrorKind::NotFound,
+            None,
+            format!("Could not determine app data directory: {}", e),
+        )
+    })?;
+    fs::create_dir_all(&dir).map_err(|e| FontError::io(&dir, "Failed to create directory", &e))?;
+    Ok(dir)
+}
+
+// A missing file reads as the default value, so first runs need no special casing
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, FontError> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(FontError::io(path, "Failed to read", &e)),
+    };
+    serde_json::from_slice(&data).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(path),
+            format!("Failed to parse {}: {}", path.display(), e),
+        )
+    })
+}
+
+// Written to a sibling temp file and renamed over the target, so a crash never leaves half a file
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), FontError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| FontError::io(parent, "Failed to create directory", &e))?;
+    }
+    let data = serde_json::to_vec_pretty(value).map_err(|e| {
+        FontError::new(FontErrorKind::Io, Some(path), format!("Failed to serialize {}: {}", path.display(), e))
+    })?;
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).map_err(|e| FontError::io(&tmp, "Failed to write", &e))?;
+    fs::rename(&tmp, path).map_err(|e| FontError::io(path, "Failed to replace", &e))
+}
+
+// Moves a file, falling back to copy + delete when the target is on another volume
+pub fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        // ERROR_NOT_SAME_DEVICE
+        Err(e) if e.raw_os_error() == Some(17) => {
+            fs::copy(from, to)?;
+            fs::remove_file(from).inspect_err(|_| {
+                let _ = fs::remove_file(to);
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
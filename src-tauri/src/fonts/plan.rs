@@ -0,0 +1,407 @@
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::enumerate::{font_registrations, installed_font_database};
+use crate::fonts::install::{
+    calculate_file_hash, full_font_name, same_path, unique_registry_name, ConflictPolicy, InstallOptions, InstallScope,
+};
+use crate::fonts::validate::{validate_font_file, ValidationIssue};
+use crate::fonts::version::read_font_version_file;
+use crate::fonts::resolve_registry_path;
+use crate::win::registry::list_font_registry_values;
+use fontdb::{Database, Source};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use ttf_parser::Face;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PlannedAction {
+    Install,
+    // Same bytes already at the destination; only the registry value is (re)written
+    SkipIdentical,
+    Overwrite,
+    KeepBoth,
+    Upgrade,
+    Skip,
+    SkipNotNewer,
+    // Another installed font already claims one of this file's PostScript names
+    Conflict,
+    Invalid,
+}
+
+impl PlannedAction {
+    // Whether executing this action copies the source into the fonts directory
+    pub fn copies_file(self) -> bool {
+        matches!(
+            self,
+            PlannedAction::Install | PlannedAction::Overwrite | PlannedAction::KeepBoth | PlannedAction::Upgrade
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PostscriptConflict {
+    pub postscript_name: String,
+    pub installed_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedInstall {
+    pub source: String,
+    pub action: PlannedAction,
+    pub destination: Option<String>,
+    // SHA-256 of what the destination should hold when this item runs: the file found there, or
+    // the earlier source in the same batch that writes it. None means it should not exist yet.
+    #[serde(default)]
+    pub destination_hash: Option<String>,
+    pub registry_name: Option<String>,
+    pub family: Option<String>,
+    pub postscript_names: Vec<String>,
+    pub conflict: Option<PostscriptConflict>,
+    pub message: Option<String>,
+    pub error: Option<FontError>,
+    #[serde(default)]
+    pub warnings: Vec<ValidationIssue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPlan {
+    pub fonts_dir: String,
+    pub conflict_policy: ConflictPolicy,
+    #[serde(default)]
+    pub atomic: bool,
+    #[serde(default)]
+    pub scope: InstallScope,
+    pub items: Vec<PlannedInstall>,
+}
+
+// Dry run of install_fonts: everything is resolved, nothing is written
+#[tauri::command]
+pub async fn plan_install(paths: Vec<String>, options: Option<InstallOptions>) -> InstallPlan {
+    build_plan(&paths, &options.unwrap_or_default(), None)
+}
+
+// Once `cancel` is set the remaining files are left unplanned; executing the plan then reports
+// them as cancelled
+pub fn build_plan(paths: &[String], options: &InstallOptions, cancel: Option<&AtomicBool>) -> InstallPlan {
+    let fonts_dir = options.scope.fonts_dir();
+    let mut planner = Planner {
+        fonts_dir: &fonts_dir,
+        policy: options.conflict_policy,
+        installed: installed_postscript_names(),
+        planned: HashMap::new(),
+        registered: list_font_registry_values(options.scope.hive()).unwrap_or_default(),
+        registry_names: Vec::new(),
+    };
+
+    let items = if options.scope == InstallScope::CurrentUser && std::env::var("LOCALAPPDATA").unwrap_or_default().is_empty() {
+        paths
+            .iter()
+            .map(|p| {
+                let error = FontError::new(
+                    FontErrorKind::NotFound,
+                    None,
+                    "Could not determine LOCALAPPDATA directory",
+                );
+                invalid(p, error, Vec::new())
+            })
+            .collect()
+    } else {
+        paths
+            .iter()
+            .map(|p| {
+                if cancel.is_some_and(|c| c.load(AtomicOrdering::SeqCst)) {
+                    not_planned(p)
+                } else {
+                    planner.plan_one(p)
+                }
+            })
+            .collect()
+    };
+
+    InstallPlan {
+        fonts_dir: fonts_dir.to_string_lossy().to_string(),
+        conflict_policy: options.conflict_policy,
+        atomic: options.atomic,
+        scope: options.scope,
+        items,
+    }
+}
+
+struct Planner<'a> {
+    fonts_dir: &'a Path,
+    policy: ConflictPolicy,
+    // PostScript name -> file of every font Windows currently loads
+    installed: HashMap<String, PathBuf>,
+    // Lowercased destination -> source, for files earlier in the same batch
+    planned: HashMap<String, PathBuf>,
+    // Value name and data of every font registered in the scope's hive
+    registered: Vec<(String, String)>,
+    // Value names claimed by files earlier in the same batch
+    registry_names: Vec<String>,
+}
+
+impl Planner<'_> {
+    fn plan_one(&mut self, path_str: &str) -> PlannedInstall {
+        let path = Path::new(path_str);
+        if !path.exists() {
+            let error = FontError::new(FontErrorKind::NotFound, Some(path), format!("File not found: {}", path_str));
+            return invalid(path_str, error, Vec::new());
+        }
+
+        // Basic validation
+        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+        if !matches!(ext.as_deref(), Some("ttf") | Some("otf")) {
+            let error = FontError::new(
+                FontErrorKind::UnsupportedFormat,
+                Some(path),
+                format!("Unsupported file type: {}", path_str),
+            );
+            return invalid(path_str, error, Vec::new());
+        }
+
+        // Health check: refuse files that are structurally broken, keep warnings for the report
+        let report = validate_font_file(path);
+        let warnings: Vec<ValidationIssue> = report.warnings().cloned().collect();
+        if report.has_errors() {
+            let reasons: Vec<&str> = report.errors().map(|i| i.message.as_str()).collect();
+            let error = FontError::new(
+                FontErrorKind::InvalidFont,
+                Some(path),
+                format!("Font validation failed for {}: {}", path_str, reasons.join("; ")),
+            );
+            return invalid(path_str, error, warnings);
+        }
+
+        // Parse font metadata to get family name (needed for registry key)
+        // Optimization: scope the Database usage to ensure file handle is dropped as soon as possible
+        let (family, full_name, postscript_names) = {
+            let mut db = Database::new();
+            if let Err(e) = db.load_font_file(path) {
+                let error = FontError::new(
+                    FontErrorKind::ParseFailed,
+                    Some(path),
+                    format!("Failed to parse font file {}: {}", path_str, e),
+                );
+                return invalid(path_str, error, warnings);
+            }
+
+            // Use the first face to determine the registry name
+            let (family, full_name) = match db.faces().next() {
+                Some(face) => {
+                    let family = face.families.first().map(|f| f.0.clone()).unwrap_or("Unknown".to_string());
+                    let full_name = db
+                        .with_face_data(face.id, |data, index| Face::parse(data, index).ok().and_then(|f| full_font_name(&f)))
+                        .flatten();
+                    (family, full_name)
+                }
+                None => {
+                    let error = FontError::new(
+                        FontErrorKind::ParseFailed,
+                        Some(path),
+                        format!("No font faces found in file: {}", path_str),
+                    );
+                    return invalid(path_str, error, warnings);
+                }
+            };
+            let names: Vec<String> = db.faces().map(|f| f.post_script_name.clone()).collect();
+            (family, full_name, names)
+        };
+
+        let file_name = path.file_name().unwrap();
+        let mut dest_path = self.fonts_dir.join(file_name);
+        let dest_key = dest_path.to_string_lossy().to_lowercase();
+
+        let conflict = postscript_names.iter().find_map(|name| {
+            let installed = self.installed.get(name)?;
+            if installed.to_string_lossy().to_lowercase() == dest_key {
+                return None;
+            }
+            Some(PostscriptConflict {
+                postscript_name: name.clone(),
+                installed_path: installed.to_string_lossy().to_string(),
+            })
+        });
+
+        // What currently occupies the destination: an earlier file in this batch, or a file on disk
+        let from_batch = self.planned.get(&dest_key).cloned();
+        let existing = from_batch.clone().or_else(|| dest_path.exists().then(|| dest_path.clone()));
+        let existing_hash = existing.as_deref().and_then(|p| calculate_file_hash(p).ok());
+
+        let mut message = None;
+        let mut action = PlannedAction::Install;
+
+        if let Some(existing) = existing {
+            if files_identical(path, &existing) {
+                action = if from_batch.is_some() {
+                    message = Some(format!("Duplicate of {} earlier in this batch", existing.display()));
+                    PlannedAction::Skip
+                } else {
+                    PlannedAction::SkipIdentical
+                };
+            } else {
+                // Exists with different content: the caller's policy decides
+                action = match self.policy {
+                    ConflictPolicy::Skip => {
+                        message = Some("A different font file with the same name is already installed".to_string());
+                        PlannedAction::Skip
+                    }
+                    ConflictPolicy::Overwrite => PlannedAction::Overwrite,
+                    ConflictPolicy::KeepBoth => {
+                        dest_path = self.unique_destination(path);
+                        PlannedAction::KeepBoth
+                    }
+                    ConflictPolicy::UpgradeIfNewer => {
+                        if is_newer(path, &existing) {
+                            PlannedAction::Upgrade
+                        } else {
+                            message = Some("The installed version is the same or newer".to_string());
+                            PlannedAction::SkipNotNewer
+                        }
+                    }
+                };
+            }
+        }
+
+        // Don't introduce a second copy of a PostScript name unless the policy allows replacing things
+        if let Some(c) = &conflict {
+            let blocked = match self.policy {
+                ConflictPolicy::Skip => true,
+                ConflictPolicy::UpgradeIfNewer => !is_newer(path, Path::new(&c.installed_path)),
+                _ => false,
+            };
+            if action.copies_file() && blocked {
+                action = PlannedAction::Conflict;
+            }
+            if message.is_none() {
+                message = Some(format!(
+                    "PostScript name {} is already used by {}",
+                    c.postscript_name, c.installed_path
+                ));
+            }
+        }
+
+        // Named after the full name, e.g. "Arial Bold (TrueType)". A name another file holds, in the
+        // hive or earlier in this batch, gets the destination's stem added; KeepBoth always lands here.
+        let stem = dest_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let registry_name = unique_registry_name(full_name.as_deref().unwrap_or(&family), &stem, |name| {
+            self.registry_name_taken(name, &dest_path)
+        });
+
+        if action.copies_file() {
+            self.planned
+                .insert(dest_path.to_string_lossy().to_lowercase(), path.to_path_buf());
+        }
+        if action.copies_file() || action == PlannedAction::SkipIdentical {
+            self.registry_names.push(registry_name.clone());
+        }
+
+        PlannedInstall {
+            source: path_str.to_string(),
+            action,
+            destination: Some(dest_path.to_string_lossy().to_string()),
+            // KeepBoth picked a free name
+            destination_hash: if action == PlannedAction::KeepBoth { None } else { existing_hash },
+            registry_name: Some(registry_name),
+            family: Some(family),
+            postscript_names,
+            conflict,
+            message,
+            error: None,
+            warnings,
+        }
+    }
+
+    fn registry_name_taken(&self, name: &str, dest: &Path) -> bool {
+        self.registry_names.iter().any(|n| n.eq_ignore_ascii_case(name))
+            || self
+                .registered
+                .iter()
+                .any(|(n, data)| n.eq_ignore_ascii_case(name) && !same_path(&resolve_registry_path(data), dest))
+    }
+
+    // "Foo.ttf" -> "Foo (2).ttf", "Foo (3).ttf", ... whichever is free first, on disk and in this batch
+    fn unique_destination(&self, source: &Path) -> PathBuf {
+        let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = source.extension().unwrap_or_default().to_string_lossy();
+        let mut n = 2;
+        loop {
+            let candidate = self.fonts_dir.join(format!("{} ({}).{}", stem, n, ext));
+            let key = candidate.to_string_lossy().to_lowercase();
+            if !candidate.exists() && !self.planned.contains_key(&key) {
+                return candidate;
+            }
+            n += 1;
+        }
+    }
+}
+
+fn invalid(path_str: &str, error: FontError, warnings: Vec<ValidationIssue>) -> PlannedInstall {
+    PlannedInstall {
+        source: path_str.to_string(),
+        action: PlannedAction::Invalid,
+        destination: None,
+        destination_hash: None,
+        registry_name: None,
+        family: None,
+        postscript_names: Vec::new(),
+        conflict: None,
+        message: Some(error.message.clone()),
+        error: Some(error),
+        warnings,
+    }
+}
+
+fn not_planned(path_str: &str) -> PlannedInstall {
+    PlannedInstall {
+        source: path_str.to_string(),
+        action: PlannedAction::Skip,
+        destination: None,
+        destination_hash: None,
+        registry_name: None,
+        family: None,
+        postscript_names: Vec::new(),
+        conflict: None,
+        message: Some("Cancelled before this file was checked".to_string()),
+        error: None,
+        warnings: Vec::new(),
+    }
+}
+
+fn files_identical(a: &Path, b: &Path) -> bool {
+    // First check file size, then hash
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a_meta), Ok(b_meta)) if a_meta.len() == b_meta.len() => {
+            matches!(
+                (calculate_file_hash(a), calculate_file_hash(b)),
+                (Ok(a_hash), Ok(b_hash)) if a_hash == b_hash
+            )
+        }
+        _ => false,
+    }
+}
+
+fn is_newer(candidate: &Path, installed: &Path) -> bool {
+    match (read_font_version_file(candidate), read_font_version_file(installed)) {
+        (Some(new), Some(old)) => new.compare(&old) == Ordering::Greater,
+        _ => false,
+    }
+}
+
+fn installed_postscript_names() -> HashMap<String, PathBuf> {
+    let db = installed_font_database(&font_registrations());
+
+    let mut names = HashMap::new();
+    for face in db.faces() {
+        if let Source::File(path) | Source::SharedFile(path, _) = &face.source {
+            names.entry(face.post_script_name.clone()).or_insert_with(|| path.clone());
+        }
+    }
+    names
+}
@@ -1,6 +1,8 @@
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::{system_fonts_dir, user_fonts_dir};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde::Serialize;
-use std::{fs, path::Path};
+use std::{fs, path::{Path, PathBuf}};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,11 +12,11 @@ pub struct FontFileBase64Result {
 }
 
 #[tauri::command]
-pub fn read_font_file_base64(path: String) -> Result<FontFileBase64Result, String> {
+pub fn read_font_file_base64(path: String) -> Result<FontFileBase64Result, FontError> {
     let p = Path::new(&path);
 
     if !p.exists() {
-        return Err("Font file not found".to_string());
+        return Err(FontError::new(FontErrorKind::NotFound, Some(p), "Font file not found"));
     }
 
     let ext = p
@@ -24,39 +26,19 @@ pub fn read_font_file_base64(path: String) -> Result<FontFileBase64Result, Strin
         .to_lowercase();
 
     if ext != "ttf" && ext != "otf" {
-        return Err("Unsupported file type".to_string());
+        return Err(FontError::new(FontErrorKind::UnsupportedFormat, Some(p), "Unsupported file type"));
     }
 
-    let canonical = p
-        .canonicalize()
-        .map_err(|e| format!("Failed to resolve font path: {}", e))?;
-
-    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
-    let system_fonts_dir = Path::new(&system_root).join("Fonts");
-
-    let local_app_data = std::env::var("LOCALAPPDATA").unwrap_or_default();
-    let user_fonts_dir = Path::new(&local_app_data)
-        .join("Microsoft")
-        .join("Windows")
-        .join("Fonts");
-
-    let system_fonts_dir = system_fonts_dir
-        .canonicalize()
-        .unwrap_or(system_fonts_dir);
-    let user_fonts_dir = user_fonts_dir.canonicalize().unwrap_or(user_fonts_dir);
-
-    if !canonical.starts_with(&system_fonts_dir) && !canonical.starts_with(&user_fonts_dir) {
-        return Err("Font path is not in an allowed directory".to_string());
-    }
+    let canonical = allowed_font_path(p)?;
 
     let meta = fs::metadata(&canonical)
-        .map_err(|e| format!("Failed to read font metadata: {}", e))?;
+        .map_err(|e| FontError::io(p, "Failed to read font metadata", &e))?;
 
     if meta.len() > 25 * 1024 * 1024 {
-        return Err("Font file too large".to_string());
+        return Err(FontError::new(FontErrorKind::TooLarge, Some(p), "Font file too large"));
     }
 
-    let bytes = fs::read(&canonical).map_err(|e| format!("Failed to read font file: {}", e))?;
+    let bytes = fs::read(&canonical).map_err(|e| FontError::io(p, "Failed to read font file", &e))?;
     let mime = if ext == "otf" { "font/otf" } else { "font/ttf" }.to_string();
 
     Ok(FontFileBase64Result {
@@ -65,3 +47,26 @@ pub fn read_font_file_base64(path: String) -> Result<FontFileBase64Result, Strin
     })
 }
 
+// Resolves a path handed in by the frontend, rejecting anything outside the system and per-user
+// font folders
+pub(crate) fn allowed_font_path(p: &Path) -> Result<PathBuf, FontError> {
+    let canonical = p
+        .canonicalize()
+        .map_err(|e| FontError::io(p, "Failed to resolve font path", &e))?;
+
+    let system_fonts_dir = system_fonts_dir();
+    let system_fonts_dir = system_fonts_dir
+        .canonicalize()
+        .unwrap_or(system_fonts_dir);
+    let user_fonts_dir = user_fonts_dir();
+    let user_fonts_dir = user_fonts_dir.canonicalize().unwrap_or(user_fonts_dir);
+
+    if !canonical.starts_with(&system_fonts_dir) && !canonical.starts_with(&user_fonts_dir) {
+        return Err(FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            Some(p),
+            "Font path is not in an allowed directory",
+        ));
+    }
+    Ok(canonical)
+}
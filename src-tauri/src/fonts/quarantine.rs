@@ -0,0 +1,249 @@
+use crate::fonts::enumerate::invalidate_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::persist::{app_data_dir, move_file, write_json};
+use crate::win::broadcast_font_change;
+use crate::win::registry::{delete_font_registry_value, register_font, FontHive};
+use fontdb::Database;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+const ENTRY_FILE: &str = "entry.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedRegistryValue {
+    pub name: String,
+    pub data: String,
+}
+
+// One removed font: <app data>/quarantine/<id>/ holds the file and this entry as entry.json
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineEntry {
+    pub id: String,
+    pub original_path: String,
+    pub file_name: String,
+    pub registry_values: Vec<QuarantinedRegistryValue>,
+    pub family: Option<String>,
+    pub postscript_names: Vec<String>,
+    pub size: u64,
+    // Unix timestamp in seconds
+    pub removed_at: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineActionResult {
+    pub id: String,
+    pub success: bool,
+    pub error: Option<FontError>,
+}
+
+#[tauri::command]
+pub fn list_quarantine(app: AppHandle) -> Result<Vec<QuarantineEntry>, FontError> {
+    let dir = quarantine_dir(&app)?;
+    let mut entries: Vec<QuarantineEntry> = fs::read_dir(&dir)
+        .map_err(|e| FontError::io(&dir, "Failed to read", &e))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| read_entry(&e.path()).ok())
+        .collect();
+
+    // Most recent removals first
+    entries.sort_by_key(|e| std::cmp::Reverse(e.removed_at));
+    Ok(entries)
+}
+
+// Moves files back to where they were and re-creates their registry values
+#[tauri::command]
+pub fn restore_quarantined_fonts(app: AppHandle, ids: Vec<String>) -> Result<Vec<QuarantineActionResult>, FontError> {
+    let dir = quarantine_dir(&app)?;
+    let results: Vec<QuarantineActionResult> = ids
+        .into_iter()
+        .map(|id| {
+            let error = entry_dir(&dir, &id).and_then(|entry_dir| restore_one(&entry_dir)).err();
+            QuarantineActionResult {
+                id,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect();
+
+    if results.iter().any(|r| r.success) {
+        broadcast_font_change();
+        invalidate_variants(&app);
+    }
+    Ok(results)
+}
+
+// Permanently deletes the given entries, or everything when no ids are passed
+#[tauri::command]
+pub fn purge_quarantine(app: AppHandle, ids: Option<Vec<String>>) -> Result<Vec<QuarantineActionResult>, FontError> {
+    let dir = quarantine_dir(&app)?;
+    let ids = match ids {
+        Some(ids) => ids,
+        None => list_quarantine(app)?.into_iter().map(|e| e.id).collect(),
+    };
+
+    Ok(ids
+        .into_iter()
+        .map(|id| {
+            let error = entry_dir(&dir, &id)
+                .and_then(|entry_dir| {
+                    read_entry(&entry_dir)?;
+                    fs::remove_dir_all(&entry_dir).map_err(|e| FontError::io(&entry_dir, "Failed to delete", &e))
+                })
+                .err();
+            QuarantineActionResult {
+                id,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect())
+}
+
+// Moves an already unregistered font into the quarantine. On failure the file stays where it was.
+// A file that is in use gets copied instead; the bool tells the caller the original is still on disk.
+pub fn quarantine_font(
+    app: &AppHandle,
+    font_path: &Path,
+    registry_values: Vec<(String, String)>,
+) -> Result<(QuarantineEntry, bool), FontError> {
+    let dir = quarantine_dir(app)?;
+    let file_name = font_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "font".to_string());
+
+    // Read what we can about the font before it moves; a damaged file is still quarantined
+    let (family, postscript_names) = {
+        let mut db = Database::new();
+        match db.load_font_file(font_path) {
+            Ok(()) => (
+                db.faces().next().and_then(|f| f.families.first()).map(|f| f.0.clone()),
+                db.faces().map(|f| f.post_script_name.clone()).collect(),
+            ),
+            Err(_) => (None, Vec::new()),
+        }
+    };
+    let size = fs::metadata(font_path).map(|m| m.len()).unwrap_or(0);
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut nanos = now.as_nanos();
+    let mut entry_dir = dir.join(format!("{:x}", nanos));
+    while entry_dir.exists() {
+        nanos += 1;
+        entry_dir = dir.join(format!("{:x}", nanos));
+    }
+    fs::create_dir_all(&entry_dir).map_err(|e| FontError::io(&entry_dir, "Failed to create directory", &e))?;
+
+    let entry = QuarantineEntry {
+        id: format!("{:x}", nanos),
+        original_path: font_path.to_string_lossy().to_string(),
+        file_name: file_name.clone(),
+        registry_values: registry_values
+            .into_iter()
+            .map(|(name, data)| QuarantinedRegistryValue { name, data })
+            .collect(),
+        family,
+        postscript_names,
+        size,
+        removed_at: now.as_secs(),
+    };
+
+    let stored = entry_dir.join(&file_name);
+    let result = write_json(&entry_dir.join(ENTRY_FILE), &entry).and_then(|_| {
+        match move_file(font_path, &stored).map_err(|e| FontError::io(font_path, "Failed to move", &e)) {
+            Ok(()) => Ok(false),
+            // Loaded fonts can usually still be read
+            Err(error) if error.kind == FontErrorKind::FileInUse => fs::copy(font_path, &stored)
+                .map(|_| true)
+                .map_err(|_| error),
+            Err(error) => Err(error),
+        }
+    });
+    match result {
+        Ok(left_behind) => Ok((entry, left_behind)),
+        Err(error) => {
+            let _ = fs::remove_dir_all(&entry_dir);
+            Err(error)
+        }
+    }
+}
+
+// Drops an entry whose file never left its original place, e.g. when removing the font is rolled back
+pub fn discard_quarantined(app: &AppHandle, id: &str) -> Result<(), FontError> {
+    let entry_dir = entry_dir(&quarantine_dir(app)?, id)?;
+    fs::remove_dir_all(&entry_dir).map_err(|e| FontError::io(&entry_dir, "Failed to delete", &e))
+}
+
+fn restore_one(entry_dir: &Path) -> Result<(), FontError> {
+    let entry = read_entry(entry_dir)?;
+    let original = PathBuf::from(&entry.original_path);
+    if original.exists() {
+        return Err(FontError::new(
+            FontErrorKind::AlreadyExists,
+            Some(&original),
+            format!("A file already exists at {}", original.display()),
+        ));
+    }
+
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).map_err(|e| FontError::io(parent, "Failed to create directory", &e))?;
+    }
+    let stored = entry_dir.join(&entry.file_name);
+    move_file(&stored, &original).map_err(|e| FontError::io(&stored, "Failed to restore", &e))?;
+
+    for (i, value) in entry.registry_values.iter().enumerate() {
+        if let Err(e) = register_font(FontHive::CurrentUser, &value.name, &value.data) {
+            // Put everything back the way it was so the entry can be restored again later
+            for registered in &entry.registry_values[..i] {
+                let _ = delete_font_registry_value(FontHive::CurrentUser, &registered.name);
+            }
+            let _ = move_file(&original, &stored);
+            return Err(FontError::registry(Some(&original), "Failed to restore registry value", &e));
+        }
+    }
+
+    // Only the metadata is left at this point
+    let _ = fs::remove_dir_all(entry_dir);
+    Ok(())
+}
+
+// Ids come from the frontend; only the hex names quarantine_font generates are accepted
+fn entry_dir(dir: &Path, id: &str) -> Result<PathBuf, FontError> {
+    let mut components = Path::new(id).components();
+    let valid = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+        && !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(FontError::new(
+            FontErrorKind::NotFound,
+            None,
+            format!("No quarantine entry with id {:?}", id),
+        ));
+    }
+    Ok(dir.join(id))
+}
+
+fn read_entry(entry_dir: &Path) -> Result<QuarantineEntry, FontError> {
+    let path = entry_dir.join(ENTRY_FILE);
+    let data = fs::read(&path).map_err(|e| FontError::io(&path, "Failed to read", &e))?;
+    serde_json::from_slice(&data).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(&path),
+            format!("Failed to parse {}: {}", path.display(), e),
+        )
+    })
+}
+
+fn quarantine_dir(app: &AppHandle) -> Result<PathBuf, FontError> {
+    let dir = app_data_dir(app)?.join("quarantine");
+    fs::create_dir_all(&dir).map_err(|e| FontError::io(&dir, "Failed to create directory", &e))?;
+    Ok(dir)
+}
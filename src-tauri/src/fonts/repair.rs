@@ -0,0 +1,251 @@
+use crate::fonts::enumerate::invalidate_variants;
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::install::{full_font_name, unique_registry_name};
+use crate::fonts::{resolve_registry_path, user_fonts_dir};
+use crate::win::broadcast_font_change;
+use crate::win::registry::{delete_font_registry_value, list_font_registry_values, register_font, FontHive};
+use fontdb::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use ttf_parser::Face;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RegistryIssueKind {
+    // The value points at a file that doesn't exist
+    MissingFile,
+    // A font file in the user fonts directory that no value points at
+    UnregisteredFile,
+    // A per-user value holding a bare file name; Windows resolves those against %SystemRoot%\Fonts
+    RelativePath,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryIssue {
+    pub kind: RegistryIssueKind,
+    pub hive: FontHive,
+    pub value_name: Option<String>,
+    pub value_data: Option<String>,
+    // The file the value resolves to, or the stray file
+    pub path: String,
+    // HKLM is only reported; fixing it needs elevation
+    pub repairable: bool,
+    pub message: String,
+}
+
+impl RegistryIssue {
+    fn is_same(&self, other: &RegistryIssue) -> bool {
+        self.kind == other.kind
+            && self.hive == other.hive
+            && self.value_name == other.value_name
+            && self.value_data == other.value_data
+            && self.path.to_lowercase() == other.path.to_lowercase()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryScanReport {
+    pub issues: Vec<RegistryIssue>,
+    pub scanned_values: usize,
+    pub scanned_files: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairFailure {
+    pub issue: RegistryIssue,
+    pub error: FontError,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairResult {
+    pub repaired: Vec<RegistryIssue>,
+    pub failed: Vec<RepairFailure>,
+}
+
+#[tauri::command]
+pub async fn scan_font_registry() -> Result<RegistryScanReport, FontError> {
+    scan()
+}
+
+// Fixes the given issues, or everything repairable from a fresh scan when none are passed
+#[tauri::command]
+pub async fn repair_font_registry(app: AppHandle, issues: Option<Vec<RegistryIssue>>) -> Result<RepairResult, FontError> {
+    // Passed issues may come from an older scan, so each one is acted on as a fresh scan reports it
+    let current = scan()?.issues;
+    let issues = issues.unwrap_or_else(|| current.clone());
+
+    let mut result = RepairResult {
+        repaired: Vec::new(),
+        failed: Vec::new(),
+    };
+    for issue in issues.into_iter().filter(|i| i.repairable) {
+        let outcome = match current.iter().find(|c| c.is_same(&issue)) {
+            Some(fresh) if fresh.repairable => repair_one(fresh),
+            _ => Err(FontError::new(
+                FontErrorKind::NotFound,
+                Some(Path::new(&issue.path)),
+                format!("{} no longer applies; scan again", issue.message),
+            )),
+        };
+        match outcome {
+            Ok(()) => result.repaired.push(issue),
+            Err(error) => result.failed.push(RepairFailure { issue, error }),
+        }
+    }
+
+    if !result.repaired.is_empty() {
+        broadcast_font_change();
+        invalidate_variants(&app);
+    }
+    Ok(result)
+}
+
+fn scan() -> Result<RegistryScanReport, FontError> {
+    let user_values = list_font_registry_values(FontHive::CurrentUser)
+        .map_err(|e| FontError::registry(None, "Failed to read HKCU fonts", &e))?;
+    let machine_values = list_font_registry_values(FontHive::LocalMachine)
+        .map_err(|e| FontError::registry(None, "Failed to read HKLM fonts", &e))?;
+
+    let user_dir = user_fonts_dir();
+    let mut issues = Vec::new();
+    // Lowercased resolved paths of every value, to spot files nothing points at
+    let mut registered = HashSet::new();
+
+    for (hive, values) in [(FontHive::CurrentUser, &user_values), (FontHive::LocalMachine, &machine_values)] {
+        for (name, data) in values {
+            let resolved = resolve_registry_path(data);
+            registered.insert(resolved.to_string_lossy().to_lowercase());
+
+            let issue = |kind, message: String| RegistryIssue {
+                kind,
+                hive,
+                value_name: Some(name.clone()),
+                value_data: Some(data.clone()),
+                path: resolved.to_string_lossy().to_string(),
+                repairable: hive == FontHive::CurrentUser,
+                message,
+            };
+
+            if resolved.exists() {
+                continue;
+            }
+
+            // A bare name that only exists in the user directory: the value is fixable, the file isn't lost
+            let in_user_dir = user_dir.join(data);
+            if hive == FontHive::CurrentUser && !Path::new(data).is_absolute() && in_user_dir.exists() {
+                registered.insert(in_user_dir.to_string_lossy().to_lowercase());
+                let mut relative = issue(
+                    RegistryIssueKind::RelativePath,
+                    format!("{} uses a relative path; the file is in {}", name, user_dir.display()),
+                );
+                relative.path = in_user_dir.to_string_lossy().to_string();
+                issues.push(relative);
+            } else {
+                issues.push(issue(
+                    RegistryIssueKind::MissingFile,
+                    format!("{} points to a missing file", name),
+                ));
+            }
+        }
+    }
+
+    let files = font_files(&user_dir);
+    for file in &files {
+        if !registered.contains(&file.to_string_lossy().to_lowercase()) {
+            issues.push(RegistryIssue {
+                kind: RegistryIssueKind::UnregisteredFile,
+                hive: FontHive::CurrentUser,
+                value_name: None,
+                value_data: None,
+                path: file.to_string_lossy().to_string(),
+                repairable: true,
+                message: format!("{} has no registry value", file.display()),
+            });
+        }
+    }
+
+    Ok(RegistryScanReport {
+        issues,
+        scanned_values: user_values.len() + machine_values.len(),
+        scanned_files: files.len(),
+    })
+}
+
+fn repair_one(issue: &RegistryIssue) -> Result<(), FontError> {
+    let path = Path::new(&issue.path);
+    // Per-user values may only point into the per-user fonts directory
+    if issue.kind != RegistryIssueKind::MissingFile && path.parent() != Some(user_fonts_dir().as_path()) {
+        return Err(FontError::new(
+            FontErrorKind::OutsideAllowedDir,
+            Some(path),
+            format!("{} is not in {}", path.display(), user_fonts_dir().display()),
+        ));
+    }
+    match issue.kind {
+        RegistryIssueKind::MissingFile => {
+            let name = issue.value_name.as_deref().unwrap_or_default();
+            delete_font_registry_value(FontHive::CurrentUser, name)
+                .map_err(|e| FontError::registry(Some(path), "Failed to delete registry value", &e))
+        }
+        RegistryIssueKind::RelativePath => {
+            let name = issue.value_name.as_deref().unwrap_or_default();
+            register_font(FontHive::CurrentUser, name, &issue.path)
+                .map_err(|e| FontError::registry(Some(path), "Failed to update registry value", &e))
+        }
+        RegistryIssueKind::UnregisteredFile => {
+            let name = registry_name_for(path)?;
+            register_font(FontHive::CurrentUser, &name, &issue.path)
+                .map_err(|e| FontError::registry(Some(path), "Failed to write registry value", &e))
+        }
+    }
+}
+
+// Same naming as installs: the full name, with the file's stem added while that is taken
+fn registry_name_for(path: &Path) -> Result<String, FontError> {
+    let mut db = Database::new();
+    db.load_font_file(path).map_err(|e| {
+        FontError::new(
+            FontErrorKind::ParseFailed,
+            Some(path),
+            format!("Failed to parse font file {}: {}", path.display(), e),
+        )
+    })?;
+    let face = db.faces().next();
+    let family = face
+        .and_then(|f| f.families.first())
+        .map(|f| f.0.clone())
+        .unwrap_or("Unknown".to_string());
+    let full_name = face
+        .and_then(|f| db.with_face_data(f.id, |data, index| Face::parse(data, index).ok().and_then(|f| full_font_name(&f))))
+        .flatten();
+
+    let values = list_font_registry_values(FontHive::CurrentUser)
+        .map_err(|e| FontError::registry(Some(path), "Failed to read HKCU fonts", &e))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(unique_registry_name(full_name.as_deref().unwrap_or(&family), &stem, |name| {
+        values.iter().any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }))
+}
+
+// Font files directly in the directory; leftovers renamed aside for deletion don't count
+fn font_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            let ext = p.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+            p.is_file() && matches!(ext.as_deref(), Some("ttf") | Some("otf") | Some("ttc") | Some("otc"))
+        })
+        .collect()
+}
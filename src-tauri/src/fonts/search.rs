@@ -0,0 +1,185 @@
+use crate::fonts::classify::FontClass;
+use crate::fonts::enumerate::cached_variants;
+use crate::fonts::organize::organizer;
+use crate::fonts::{FontScope, FontVariant};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use tauri::AppHandle;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+// Every set criterion has to match; list criteria need all their entries
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchQuery {
+    // Fuzzy match against family, full, PostScript and localised names
+    pub text: Option<String>,
+    pub weight_min: Option<u16>,
+    pub weight_max: Option<u16>,
+    // "normal", "italic" or "oblique"
+    pub style: Option<String>,
+    // OS/2 width classes, 1 to 9
+    pub width_min: Option<u16>,
+    pub width_max: Option<u16>,
+    // Any of these; empty means any scope
+    pub scopes: Vec<FontScope>,
+    // Any of these
+    pub classes: Vec<FontClass>,
+    pub monospace: Option<bool>,
+    pub variable: Option<bool>,
+    pub colour: Option<bool>,
+    // Script keys as in FaceMetadata::scripts, e.g. "han"
+    pub scripts: Vec<String>,
+    // OpenType feature tags, e.g. "smcp"
+    pub features: Vec<String>,
+    // Case-insensitive OS/2 vendor id
+    pub vendor: Option<String>,
+    pub tags: Vec<String>,
+    pub include_library: bool,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    // One page, best matches first; by family, weight and style when there is no text
+    pub variants: Vec<FontVariant>,
+    // Matches across all pages
+    pub total: usize,
+    pub offset: usize,
+}
+
+#[tauri::command]
+pub async fn search_fonts(app: AppHandle, query: SearchQuery) -> SearchResult {
+    let org = if query.tags.is_empty() { Default::default() } else { organizer(&app) };
+    let text = query.text.as_deref().map(normalize).filter(|t| !t.is_empty());
+
+    let mut hits: Vec<(u32, FontVariant)> = cached_variants(&app, query.include_library)
+        .into_iter()
+        .filter(|v| matches_filters(&query, v))
+        .filter(|v| {
+            query.tags.is_empty()
+                || org.tags_for(v).is_some_and(|t| query.tags.iter().all(|tag| t.contains(tag)))
+        })
+        .filter_map(|v| match &text {
+            Some(text) => text_score(text, &v).map(|score| (score, v)),
+            None => Some((0, v)),
+        })
+        .collect();
+
+    hits.sort_by(|(sa, a), (sb, b)| {
+        Reverse(sa)
+            .cmp(&Reverse(sb))
+            .then_with(|| a.family.cmp(&b.family))
+            .then_with(|| a.weight.cmp(&b.weight))
+            .then_with(|| a.style.cmp(&b.style))
+    });
+
+    let total = hits.len();
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    SearchResult {
+        variants: hits.into_iter().skip(query.offset).take(limit).map(|(_, v)| v).collect(),
+        total,
+        offset: query.offset,
+    }
+}
+
+fn matches_filters(query: &SearchQuery, v: &FontVariant) -> bool {
+    let meta = &v.metadata;
+    query.weight_min.is_none_or(|min| v.weight >= min)
+        && query.weight_max.is_none_or(|max| v.weight <= max)
+        && query.style.as_ref().is_none_or(|style| v.style.eq_ignore_ascii_case(style))
+        && query.width_min.is_none_or(|min| meta.width >= min)
+        && query.width_max.is_none_or(|max| meta.width <= max)
+        && (query.scopes.is_empty() || query.scopes.contains(&v.scope))
+        && (query.classes.is_empty() || query.classes.contains(&v.classification))
+        && query.monospace.is_none_or(|m| meta.monospace == m)
+        && query.variable.is_none_or(|m| meta.variable == m)
+        && query.colour.is_none_or(|m| meta.colour == m)
+        && query.scripts.iter().all(|s| meta.scripts.iter().any(|own| own.eq_ignore_ascii_case(s)))
+        && query.features.iter().all(|f| meta.features.contains(f))
+        && query
+            .vendor
+            .as_ref()
+            .is_none_or(|vendor| meta.vendor.as_ref().is_some_and(|own| own.eq_ignore_ascii_case(vendor.trim())))
+}
+
+// Best score over the variant's names, or None if no name matches
+fn text_score(text: &str, v: &FontVariant) -> Option<u32> {
+    [Some(&v.family), v.full_name.as_ref(), v.postscript_name.as_ref()]
+        .into_iter()
+        .flatten()
+        .chain(&v.metadata.localized_names)
+        .filter_map(|name| name_score(text, &normalize(name)))
+        .max()
+}
+
+// Exact beats prefix beats substring beats the query's characters appearing in order,
+// and for the latter, the tighter they sit the better
+fn name_score(text: &str, name: &str) -> Option<u32> {
+    if name == text {
+        return Some(1000);
+    }
+    if name.starts_with(text) {
+        return Some(800);
+    }
+    if name.contains(text) {
+        return Some(600);
+    }
+
+    let mut chars = name.chars().enumerate();
+    let mut first = None;
+    let mut last = 0;
+    for wanted in text.chars() {
+        let (i, _) = chars.by_ref().find(|&(_, c)| c == wanted)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+    let span = (last - first? + 1) as u32;
+    Some(400 * text.chars().count() as u32 / span)
+}
+
+// Lowercase with spaces, hyphens and underscores removed, so "Noto Sans" finds "NotoSans-Regular"
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_drops_separators_and_case() {
+        assert_eq!(normalize("Noto Sans"), "notosans");
+        assert_eq!(normalize("NotoSans-Regular"), "notosansregular");
+        assert_eq!(normalize("Source_Code Pro"), "sourcecodepro");
+        assert_eq!(normalize("ÉLAN"), "élan");
+    }
+
+    #[test]
+    fn exact_prefix_and_substring_rank_in_order() {
+        assert_eq!(name_score("arial", "arial"), Some(1000));
+        assert_eq!(name_score("arial", "arialblack"), Some(800));
+        assert_eq!(name_score("black", "arialblack"), Some(600));
+    }
+
+    #[test]
+    fn subsequence_scores_by_how_tight_the_match_is() {
+        // t, g and l at 2, 10 and 12: three characters over a span of eleven
+        assert_eq!(name_score("tgl", "notosansregular"), Some(400 * 3 / 11));
+        // n, t and s at 0, 2 and 4: a span of five
+        assert_eq!(name_score("nts", "notosans"), Some(400 * 3 / 5));
+        assert!(name_score("nts", "notosans") < Some(600));
+    }
+
+    #[test]
+    fn missing_characters_do_not_match() {
+        assert_eq!(name_score("xyz", "arial"), None);
+        // Characters have to appear in order
+        assert_eq!(name_score("la", "al"), None);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::fonts::classify::FontClass;
+use crate::fonts::enumerate::{cached_variants, VariantCache};
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::FontVariant;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+use ttf_parser::{Face, OutlineBuilder, Tag};
+
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarFont {
+    pub variant: FontVariant,
+    // 0 to 1, higher is closer
+    pub similarity: f32,
+}
+
+// Design proportions of one face, each normalised so faces of any unitsPerEm compare
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Profile {
+    // PANOSE digits of a Latin text face
+    panose: Option<[u8; 10]>,
+    // x-height over cap height
+    x_to_cap: Option<f32>,
+    // OS/2 xAvgCharWidth over unitsPerEm
+    width: Option<f32>,
+    // 1 - thinnest over thickest stroke of "o": 0 for monoline, towards 1 for high contrast
+    contrast: Option<f32>,
+}
+
+// Installed faces of other families ranked by how close their proportions are to the given
+// variant's. The variant itself may be a library font, e.g. a client's font imported for reference.
+#[tauri::command]
+pub async fn find_similar_fonts(
+    app: AppHandle,
+    variant_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SimilarFont>, FontError> {
+    let variants = cached_variants(&app, true);
+    let target = variants.iter().find(|v| v.id == variant_id).cloned().ok_or_else(|| {
+        FontError::new(
+            FontErrorKind::NotFound,
+            None,
+            format!("No font variant with id {}", variant_id),
+        )
+    })?;
+
+    // Only faces not profiled before are read; collections share one read
+    let cache = app.state::<VariantCache>();
+    let mut profiles = cache.profiles.lock().unwrap();
+    let mut files: HashMap<String, Option<Vec<u8>>> = HashMap::new();
+    let mut profile_of = |v: &FontVariant| {
+        if let Some(known) = profiles.get(&v.identity) {
+            return known.clone();
+        }
+        let data = files
+            .entry(v.path.clone())
+            .or_insert_with(|| std::fs::read(Path::new(&v.path)).ok());
+        // A file that can't be read now isn't remembered, so a later search tries again
+        match data.as_deref().and_then(|data| Face::parse(data, v.face_index).ok()) {
+            Some(face) => profiles.entry(v.identity.clone()).or_insert(profile(&face)).clone(),
+            None => Profile::default(),
+        }
+    };
+
+    let reference = profile_of(&target);
+    let mut ranked: Vec<SimilarFont> = variants
+        .into_iter()
+        .filter(|v| v.installed && v.family != target.family)
+        .map(|v| {
+            let similarity = similarity(&target, &reference, &v, &profile_of(&v));
+            SimilarFont { variant: v, similarity }
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    ranked.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(ranked)
+}
+
+fn profile(face: &Face) -> Profile {
+    let upem = face.units_per_em() as f32;
+    let os2 = face.raw_face().table(Tag::from_bytes(b"OS/2"));
+    let glyph_height = |c: char| {
+        face.glyph_index(c)
+            .and_then(|g| face.glyph_bounding_box(g))
+            .map(|rect| rect.y_max)
+    };
+
+    let x_height = face.x_height().filter(|&h| h > 0).or_else(|| glyph_height('x'));
+    let cap_height = face.capital_height().filter(|&h| h > 0).or_else(|| glyph_height('H'));
+
+    Profile {
+        panose: os2
+            .and_then(|t| t.get(32..42))
+            .and_then(|p| <[u8; 10]>::try_from(p).ok())
+            .filter(|p| p[0] == 2),
+        x_to_cap: match (x_height, cap_height) {
+            (Some(x), Some(cap)) if cap > 0 => Some(x as f32 / cap as f32),
+            _ => None,
+        },
+        width: os2
+            .and_then(|t| t.get(2..4))
+            .map(|w| i16::from_be_bytes([w[0], w[1]]))
+            .filter(|&w| w > 0)
+            .map(|w| w as f32 / upem),
+        contrast: stroke_contrast(face),
+    }
+}
+
+// Weighted distance over the descriptors both faces have; a missing one counts as half way
+fn similarity(a: &FontVariant, pa: &Profile, b: &FontVariant, pb: &Profile) -> f32 {
+    fn scaled(a: Option<f32>, b: Option<f32>, range: f32) -> f32 {
+        match (a, b) {
+            (Some(a), Some(b)) => ((a - b).abs() / range).min(1.0),
+            _ => 0.5,
+        }
+    }
+
+    let panose = match (pa.panose, pb.panose) {
+        (Some(a), Some(b)) => {
+            // Digits 0 and 1 mean "any" and "no fit"; only compare what both faces state
+            let stated: Vec<(u8, u8)> = a[1..]
+                .iter()
+                .zip(&b[1..])
+                .map(|(&x, &y)| (x, y))
+                .filter(|&(x, y)| x > 1 && y > 1)
+                .collect();
+            if stated.is_empty() {
+                0.5
+            } else {
+                stated.iter().filter(|(x, y)| x != y).count() as f32 / stated.len() as f32
+            }
+        }
+        _ => 0.5,
+    };
+    let class = match (a.classification, b.classification) {
+        (FontClass::Unknown, _) | (_, FontClass::Unknown) => 0.5,
+        (x, y) if x == y => 0.0,
+        _ => 1.0,
+    };
+    let weight = ((a.weight as f32 - b.weight as f32).abs() / 800.0).min(1.0);
+    let style = if a.style == b.style { 0.0 } else { 1.0 };
+
+    let distance = 0.25 * panose
+        + 0.2 * scaled(pa.x_to_cap, pb.x_to_cap, 0.3)
+        + 0.15 * scaled(pa.contrast, pb.contrast, 1.0)
+        + 0.15 * scaled(pa.width, pb.width, 0.3)
+        + 0.1 * class
+        + 0.1 * weight
+        + 0.05 * style;
+    1.0 - distance
+}
+
+// Measures the "o": stroke thickness across its horizontal and vertical middle lines.
+// Stressed designs (Didone, most serifs) are thick at the sides and thin at top and bottom.
+fn stroke_contrast(face: &Face) -> Option<f32> {
+    let glyph = face.glyph_index('o')?;
+    let mut outline = Segments::default();
+    let bbox = face.outline_glyph(glyph, &mut outline)?;
+
+    let mid_y = (bbox.y_min as f32 + bbox.y_max as f32) / 2.0;
+    let mid_x = (bbox.x_min as f32 + bbox.x_max as f32) / 2.0;
+    let side = outline.stroke_at(|(x0, y0), (x1, y1)| crossing(y0, y1, mid_y).map(|t| x0 + t * (x1 - x0)))?;
+    let top = outline.stroke_at(|(x0, y0), (x1, y1)| crossing(x0, x1, mid_x).map(|t| y0 + t * (y1 - y0)))?;
+
+    let (thin, thick) = if side < top { (side, top) } else { (top, side) };
+    (thick > 0.0).then(|| 1.0 - thin / thick)
+}
+
+// Where along a segment from a to b the value v is crossed, as a fraction
+fn crossing(a: f32, b: f32, v: f32) -> Option<f32> {
+    ((a <= v && v < b) || (b <= v && v < a)).then(|| (v - a) / (b - a))
+}
+
+// Glyph outline flattened to line segments
+#[derive(Default)]
+struct Segments {
+    lines: Vec<((f32, f32), (f32, f32))>,
+    start: (f32, f32),
+    last: (f32, f32),
+}
+
+impl Segments {
+    // Sorted crossings of a middle line give outer edge, inner edge, inner edge, outer edge;
+    // the stroke is the first gap
+    fn stroke_at(&self, cross: impl Fn((f32, f32), (f32, f32)) -> Option<f32>) -> Option<f32> {
+        let mut hits: Vec<f32> = self.lines.iter().filter_map(|&(a, b)| cross(a, b)).collect();
+        hits.sort_by(f32::total_cmp);
+        (hits.len() >= 4).then(|| hits[1] - hits[0])
+    }
+
+    fn push(&mut self, to: (f32, f32)) {
+        self.lines.push((self.last, to));
+        self.last = to;
+    }
+}
+
+impl OutlineBuilder for Segments {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = (x, y);
+        self.last = (x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push((x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last;
+        for i in 1..=8 {
+            let t = i as f32 / 8.0;
+            let u = 1.0 - t;
+            self.push((
+                u * u * x0 + 2.0 * u * t * x1 + t * t * x,
+                u * u * y0 + 2.0 * u * t * y1 + t * t * y,
+            ));
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last;
+        for i in 1..=8 {
+            let t = i as f32 / 8.0;
+            let u = 1.0 - t;
+            self.push((
+                u * u * u * x0 + 3.0 * u * u * t * x1 + 3.0 * u * t * t * x2 + t * t * t * x,
+                u * u * u * y0 + 3.0 * u * u * t * y1 + 3.0 * u * t * t * y2 + t * t * t * y,
+            ));
+        }
+    }
+
+    fn close(&mut self) {
+        let start = self.start;
+        if self.last != start {
+            self.push(start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fonts::FontScope;
+
+    fn variant(classification: FontClass, weight: u16, style: &str) -> FontVariant {
+        FontVariant {
+            id: String::new(),
+            family: "Test".to_string(),
+            style: style.to_string(),
+            weight,
+            path: String::new(),
+            face_index: 0,
+            postscript_name: None,
+            full_name: None,
+            is_system_core: false,
+            scope: FontScope::PerUser,
+            registry_value_name: None,
+            installed: true,
+            library_hash: None,
+            identity: String::new(),
+            name_version: None,
+            classification,
+            metadata: Default::default(),
+            licence: Default::default(),
+        }
+    }
+
+    fn full_profile(panose: [u8; 10], x_to_cap: f32, width: f32, contrast: f32) -> Profile {
+        Profile {
+            panose: Some(panose),
+            x_to_cap: Some(x_to_cap),
+            width: Some(width),
+            contrast: Some(contrast),
+        }
+    }
+
+    // A closed rectangle traced through the outline builder
+    fn rect(outline: &mut Segments, x0: f32, y0: f32, x1: f32, y1: f32) {
+        outline.move_to(x0, y0);
+        outline.line_to(x1, y0);
+        outline.line_to(x1, y1);
+        outline.line_to(x0, y1);
+        outline.close();
+    }
+
+    #[test]
+    fn crossing_is_half_open_in_either_direction() {
+        assert_eq!(crossing(0.0, 10.0, 5.0), Some(0.5));
+        assert_eq!(crossing(10.0, 0.0, 5.0), Some(0.5));
+        assert_eq!(crossing(0.0, 10.0, 0.0), Some(0.0));
+        // The end point belongs to the next segment, so a shared vertex isn't counted twice
+        assert_eq!(crossing(0.0, 10.0, 10.0), None);
+        assert_eq!(crossing(3.0, 3.0, 3.0), None);
+        assert_eq!(crossing(0.0, 10.0, 11.0), None);
+    }
+
+    #[test]
+    fn stroke_at_measures_the_first_gap() {
+        // A ring 20 units thick at the sides and 10 at top and bottom
+        let mut outline = Segments::default();
+        rect(&mut outline, 0.0, 0.0, 100.0, 100.0);
+        rect(&mut outline, 20.0, 10.0, 80.0, 90.0);
+
+        let side = outline.stroke_at(|(x0, y0), (x1, y1)| crossing(y0, y1, 50.0).map(|t| x0 + t * (x1 - x0)));
+        let top = outline.stroke_at(|(x0, y0), (x1, y1)| crossing(x0, x1, 50.0).map(|t| y0 + t * (y1 - y0)));
+        assert_eq!(side, Some(20.0));
+        assert_eq!(top, Some(10.0));
+    }
+
+    #[test]
+    fn stroke_at_needs_an_inner_contour() {
+        let mut outline = Segments::default();
+        rect(&mut outline, 0.0, 0.0, 100.0, 100.0);
+        assert_eq!(
+            outline.stroke_at(|(x0, y0), (x1, y1)| crossing(y0, y1, 50.0).map(|t| x0 + t * (x1 - x0))),
+            None
+        );
+    }
+
+    #[test]
+    fn identical_faces_are_fully_similar() {
+        let v = variant(FontClass::Serif, 400, "Regular");
+        let p = full_profile([2, 2, 5, 3, 5, 4, 5, 2, 3, 4], 0.7, 0.5, 0.4);
+        assert_eq!(similarity(&v, &p, &v, &p), 1.0);
+    }
+
+    #[test]
+    fn unknown_descriptors_count_as_half_way() {
+        let v = variant(FontClass::Unknown, 400, "Regular");
+        let p = Profile::default();
+        // Everything but weight and style is unknown: 0.85 of the weight sits at 0.5
+        assert!((similarity(&v, &p, &v, &p) - 0.575).abs() < 1e-6);
+    }
+
+    #[test]
+    fn opposite_faces_are_not_similar() {
+        let a = variant(FontClass::Serif, 100, "Regular");
+        let b = variant(FontClass::Sans, 900, "Italic");
+        let pa = full_profile([2, 2, 5, 3, 5, 4, 5, 2, 3, 4], 0.5, 0.3, 0.0);
+        let pb = full_profile([2, 11, 6, 9, 6, 5, 6, 3, 4, 5], 0.9, 0.7, 1.0);
+        assert!(similarity(&a, &pa, &b, &pb).abs() < 1e-6);
+    }
+
+    #[test]
+    fn panose_ignores_any_and_no_fit_digits() {
+        let v = variant(FontClass::Sans, 400, "Regular");
+        let pa = full_profile([2, 11, 0, 1, 5, 4, 5, 2, 3, 4], 0.7, 0.5, 0.2);
+        let pb = full_profile([2, 11, 6, 9, 5, 4, 5, 2, 3, 4], 0.7, 0.5, 0.2);
+        assert_eq!(similarity(&v, &pa, &v, &pb), 1.0);
+    }
+}
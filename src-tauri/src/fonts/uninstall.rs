@@ -1,84 +1,235 @@
-use crate::win::registry::remove_font_registry_entry;
+use crate::fonts::enumerate::{installed_fonts, invalidate_variants};
+use crate::fonts::error::{FontError, FontErrorKind};
+use crate::fonts::library::library_dir;
+use crate::fonts::pending::{schedule_deletion, PendingDeletion};
+use crate::fonts::quarantine::{discard_quarantined, quarantine_font};
+use crate::fonts::{system_fonts_dir, user_fonts_dir};
+use crate::win::broadcast_font_change;
+use crate::win::registry::{register_font, remove_font_registry_entry, FontHive};
 use serde::Serialize;
-use std::fs;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
-use winapi::um::winuser::{SendMessageTimeoutW, HWND_BROADCAST, WM_FONTCHANGE, SMTO_ABORTIFHUNG};
+use tauri::AppHandle;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UninstallResult {
     pub success: bool,
     pub message: String,
+    pub error: Option<FontError>,
+    // Where the removed file went; pass to restore_quarantined_fonts to undo
+    pub quarantine_id: Option<String>,
+    // Set when the file was in use: it is unregistered but only deleted once released
+    pub pending_deletion: Option<PendingDeletion>,
 }
 
-#[tauri::command]
-pub fn uninstall_font(path: String) -> UninstallResult {
-    // 1. Safety Check: Is it a system font?
-    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
-    let system_fonts_dir = Path::new(&system_root).join("Fonts");
-    let font_path = Path::new(&path);
-    
-    if font_path.starts_with(&system_fonts_dir) {
-        return UninstallResult {
+impl UninstallResult {
+    fn failed(error: FontError) -> Self {
+        UninstallResult {
             success: false,
-            message: "Cannot uninstall system core fonts.".to_string(),
+            message: error.message.clone(),
+            error: Some(error),
+            quarantine_id: None,
+            pending_deletion: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallFileResult {
+    pub path: String,
+    // Full names of the faces in this file that were asked for
+    pub variants: Vec<String>,
+    // Faces in the same file (collections) that go away as well without being asked for
+    pub also_removed: Vec<String>,
+    pub success: bool,
+    pub error: Option<FontError>,
+    pub quarantine_id: Option<String>,
+    pub pending_deletion: Option<PendingDeletion>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUninstallResult {
+    pub success_count: usize,
+    pub failed_count: usize,
+    pub files: Vec<UninstallFileResult>,
+    // Requested ids that didn't match any installed variant
+    pub unknown_ids: Vec<String>,
+}
+
+#[tauri::command]
+pub fn uninstall_font(app: AppHandle, path: String) -> UninstallResult {
+    let removed = match remove_font(&app, Path::new(&path)) {
+        Ok(removed) => removed,
+        Err(error) => return UninstallResult::failed(error),
+    };
+
+    // 4. Broadcast Change
+    broadcast_font_change();
+    invalidate_variants(&app);
+
+    let message = if removed.pending_deletion.is_some() {
+        "Font uninstalled. The file is still in use and will be deleted once it is released."
+    } else {
+        "Font uninstalled successfully."
+    };
+    UninstallResult {
+        success: true,
+        message: message.to_string(),
+        error: None,
+        quarantine_id: Some(removed.quarantine_id),
+        pending_deletion: removed.pending_deletion,
+    }
+}
+
+// Removes every file behind the given variants, or behind a whole family, with a single broadcast at the end
+#[tauri::command]
+pub async fn uninstall_fonts(app: AppHandle, variant_ids: Option<Vec<String>>, family: Option<String>) -> BatchUninstallResult {
+    let installed = installed_fonts();
+    let ids: HashSet<String> = variant_ids.unwrap_or_default().into_iter().collect();
+
+    // Resolve the selection to unique files; collections hold several variants in one file
+    let mut selected: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut matched_ids = HashSet::new();
+    let all_variants = installed.families.iter().flat_map(|f| f.variants.iter());
+    for variant in all_variants.clone() {
+        let by_id = ids.contains(&variant.id);
+        let by_family = family.as_deref() == Some(variant.family.as_str());
+        if by_id || by_family {
+            matched_ids.insert(variant.id.clone());
+            selected
+                .entry(variant.path.clone())
+                .or_default()
+                .push(variant.full_name.clone().unwrap_or_else(|| variant.family.clone()));
+        }
+    }
+
+    let mut files = Vec::new();
+    for (path, variants) in selected {
+        let also_removed = all_variants
+            .clone()
+            .filter(|v| v.path == path && !matched_ids.contains(&v.id))
+            .map(|v| v.full_name.clone().unwrap_or_else(|| v.family.clone()))
+            .collect();
+
+        let (removed, error) = match remove_font(&app, Path::new(&path)) {
+            Ok(removed) => (Some(removed), None),
+            Err(error) => (None, Some(error)),
+        };
+        let (quarantine_id, pending_deletion) = match removed {
+            Some(r) => (Some(r.quarantine_id), r.pending_deletion),
+            None => (None, None),
         };
+        files.push(UninstallFileResult {
+            path,
+            variants,
+            also_removed,
+            success: error.is_none(),
+            error,
+            quarantine_id,
+            pending_deletion,
+        });
+    }
+
+    let success_count = files.iter().filter(|f| f.success).count();
+    if success_count > 0 {
+        broadcast_font_change();
+        invalidate_variants(&app);
+    }
+
+    let mut unknown_ids: Vec<String> = ids.into_iter().filter(|id| !matched_ids.contains(id)).collect();
+    unknown_ids.sort();
+
+    BatchUninstallResult {
+        success_count,
+        failed_count: files.len() - success_count,
+        files,
+        unknown_ids,
+    }
+}
+
+struct RemovedFont {
+    quarantine_id: String,
+    pending_deletion: Option<PendingDeletion>,
+}
+
+// Unregisters one font file and moves it into the quarantine, without broadcasting
+fn remove_font(app: &AppHandle, font_path: &Path) -> Result<RemovedFont, FontError> {
+    // 1. Safety Check: Is it a system font?
+    if font_path.starts_with(system_fonts_dir()) {
+        return Err(FontError::new(
+            FontErrorKind::ProtectedSystemFont,
+            Some(font_path),
+            "Cannot uninstall system core fonts.",
+        ));
     }
     
+    // Activated library fonts are listed as installed, but their file belongs to the library
+    if font_path.starts_with(library_dir(app)?) {
+        return Err(FontError::new(
+            FontErrorKind::FileInUse,
+            Some(font_path),
+            "Font is activated from the app library; deactivate its activation set instead.",
+        ));
+    }
+
     if !font_path.exists() {
-         return UninstallResult {
-            success: false,
-            message: "Font file not found.".to_string(),
-        };
+         return Err(FontError::new(
+            FontErrorKind::NotFound,
+            Some(font_path),
+            "Font file not found.",
+        ));
     }
 
     // 2. Remove from Registry
-    match remove_font_registry_entry(&path) {
-        Ok(found) => {
-            if !found {
-                // If not found in registry, check if it is in user fonts directory
-                 let local_app_data = std::env::var("LOCALAPPDATA").unwrap_or_default();
-                 let user_fonts_dir = Path::new(&local_app_data).join("Microsoft").join("Windows").join("Fonts");
-                 
-                 if !font_path.starts_with(&user_fonts_dir) {
-                     return UninstallResult {
-                        success: false,
-                        message: "Registry entry not found and file is not in user fonts directory.".to_string(),
-                    };
-                 }
+    let removed_values = match remove_font_registry_entry(&font_path.to_string_lossy()) {
+        Ok(values) => {
+            // If not found in registry, check if it is in user fonts directory
+            if values.is_empty() && !font_path.starts_with(user_fonts_dir()) {
+                return Err(FontError::new(
+                    FontErrorKind::NotRegistered,
+                    Some(font_path),
+                    "Registry entry not found and file is not in user fonts directory.",
+                ));
             }
+            values
         }
         Err(e) => {
-             return UninstallResult {
-                success: false,
-                message: format!("Failed to access registry: {}", e),
-            };
+             return Err(FontError::registry(Some(font_path), "Failed to access registry", &e));
         }
-    }
+    };
 
-    // 3. Delete File
-    if let Err(e) = fs::remove_file(&path) {
-         return UninstallResult {
-            success: false,
-            message: format!("Failed to delete file: {}. You might need to close applications using this font.", e),
-        };
-    }
-    
-    // 4. Broadcast Change
-    unsafe {
-        SendMessageTimeoutW(
-            HWND_BROADCAST,
-            WM_FONTCHANGE,
-            0,
-            0,
-            SMTO_ABORTIFHUNG,
-            1000,
-            std::ptr::null_mut(),
-        );
-    }
+    // 3. Move the file into the quarantine
+    let result = quarantine_font(app, font_path, removed_values.clone()).and_then(|(entry, left_behind)| {
+        if !left_behind {
+            return Ok(RemovedFont {
+                quarantine_id: entry.id,
+                pending_deletion: None,
+            });
+        }
+        // Still loaded somewhere: it stays unregistered and goes away on a later start
+        match schedule_deletion(app, font_path) {
+            Ok(pending) => Ok(RemovedFont {
+                quarantine_id: entry.id,
+                pending_deletion: Some(pending),
+            }),
+            Err(error) => {
+                let _ = discard_quarantined(app, &entry.id);
+                Err(error)
+            }
+        }
+    });
 
-    UninstallResult {
-        success: true,
-        message: "Font uninstalled successfully.".to_string(),
-    }
+    result.map_err(|mut error| {
+        // The file is still in place, so it should stay registered too
+        for (name, data) in &removed_values {
+            let _ = register_font(FontHive::CurrentUser, name, data);
+        }
+        if error.kind == FontErrorKind::FileInUse {
+            error.message.push_str(". You might need to close applications using this font.");
+        }
+        error
+    })
 }
@@ -0,0 +1,782 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use ttf_parser::{head::IndexToLocationFormat, Face, PlatformId, RawFace, Tag};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueSeverity {
+    // The file is broken badly enough that Windows would reject it or render garbage.
+    Error,
+    // The file loads, but something is off (bad checksum, odd metrics, ...).
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    Structure,
+    TableOutOfBounds,
+    Checksum,
+    MissingTable,
+    GlyphData,
+    Metrics,
+    Cmap,
+    Name,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+    pub path: String,
+    pub face_index: Option<u32>,
+    pub table: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub path: String,
+    pub face_count: u32,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Warning)
+    }
+}
+
+// Tables every face must carry for Windows to load it.
+const REQUIRED_TABLES: [&[u8; 4]; 7] = [b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2"];
+
+#[tauri::command]
+pub fn validate_font(path: String) -> ValidationReport {
+    validate_font_file(Path::new(&path))
+}
+
+pub fn validate_font_file(path: &Path) -> ValidationReport {
+    let path_str = path.to_string_lossy().to_string();
+    match fs::read(path) {
+        Ok(data) => validate_font_data(&path_str, &data),
+        Err(e) => ValidationReport {
+            path: path_str.clone(),
+            face_count: 0,
+            issues: vec![ValidationIssue {
+                severity: IssueSeverity::Error,
+                kind: IssueKind::Structure,
+                path: path_str,
+                face_index: None,
+                table: None,
+                message: format!("Failed to read file: {}", e),
+            }],
+        },
+    }
+}
+
+pub fn validate_font_data(path: &str, data: &[u8]) -> ValidationReport {
+    let mut checker = Checker {
+        path,
+        issues: Vec::new(),
+    };
+
+    // Collections carry one table directory per face, plain fonts just one
+    let face_count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+    if face_count == 0 {
+        checker.error(IssueKind::Structure, None, None, "Font collection contains no faces".to_string());
+    }
+
+    for index in 0..face_count {
+        checker.check_face(data, index, face_count > 1);
+    }
+
+    ValidationReport {
+        path: path.to_string(),
+        face_count,
+        issues: checker.issues,
+    }
+}
+
+struct Checker<'p> {
+    path: &'p str,
+    issues: Vec<ValidationIssue>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, severity: IssueSeverity, kind: IssueKind, face: Option<u32>, table: Option<&str>, message: String) {
+        self.issues.push(ValidationIssue {
+            severity,
+            kind,
+            path: self.path.to_string(),
+            face_index: face,
+            table: table.map(|t| t.to_string()),
+            message,
+        });
+    }
+
+    fn error(&mut self, kind: IssueKind, face: Option<u32>, table: Option<&str>, message: String) {
+        self.push(IssueSeverity::Error, kind, face, table, message);
+    }
+
+    fn warning(&mut self, kind: IssueKind, face: Option<u32>, table: Option<&str>, message: String) {
+        self.push(IssueSeverity::Warning, kind, face, table, message);
+    }
+
+    fn check_face(&mut self, data: &[u8], index: u32, in_collection: bool) {
+        let face_idx = Some(index);
+
+        let raw = match RawFace::parse(data, index) {
+            Ok(raw) => raw,
+            Err(e) => {
+                self.error(IssueKind::Structure, face_idx, None, format!("Invalid table directory: {}", e));
+                return;
+            }
+        };
+
+        // 1. Table directory: bounds and checksums
+        let mut intact_tables = Vec::new();
+        for record in raw.table_records {
+            let tag = tag_name(record.tag);
+            let start = record.offset as usize;
+            let end = start.checked_add(record.length as usize);
+            let table_data = match end.and_then(|end| data.get(start..end)) {
+                Some(d) => d,
+                None => {
+                    self.error(
+                        IssueKind::TableOutOfBounds,
+                        face_idx,
+                        Some(&tag),
+                        format!("Table '{}' points outside of the file", tag),
+                    );
+                    continue;
+                }
+            };
+            intact_tables.push(record.tag);
+
+            let actual = table_checksum(table_data, &record.tag.to_bytes() == b"head");
+            if actual != record.check_sum {
+                self.warning(
+                    IssueKind::Checksum,
+                    face_idx,
+                    Some(&tag),
+                    format!(
+                        "Checksum mismatch for table '{}' (stored {:08X}, computed {:08X})",
+                        tag, record.check_sum, actual
+                    ),
+                );
+            }
+        }
+
+        // The whole-file adjustment is only meaningful for standalone fonts
+        if !in_collection {
+            if let Some(head) = raw.table(Tag::from_bytes(b"head")) {
+                if let Some(stored) = read_u32(head, 8) {
+                    let expected = 0xB1B0_AFBAu32.wrapping_sub(file_checksum(data, &raw));
+                    if stored != expected {
+                        self.warning(
+                            IssueKind::Checksum,
+                            face_idx,
+                            Some("head"),
+                            "Whole-font checksum adjustment does not match file contents".to_string(),
+                        );
+                    }
+                }
+            }
+        }
+
+        // 2. Required tables
+        let has = |tag: &[u8; 4]| intact_tables.contains(&Tag::from_bytes(tag));
+        for tag in REQUIRED_TABLES {
+            if !has(tag) {
+                let name = tag_name(Tag::from_bytes(tag));
+                self.error(
+                    IssueKind::MissingTable,
+                    face_idx,
+                    Some(&name),
+                    format!("Required table '{}' is missing", name),
+                );
+            }
+        }
+        if !has(b"post") {
+            self.warning(IssueKind::MissingTable, face_idx, Some("post"), "Table 'post' is missing".to_string());
+        }
+        let has_glyf = has(b"glyf");
+        if !has_glyf && !has(b"CFF ") && !has(b"CFF2") && !has(b"EBDT") && !has(b"CBDT") && !has(b"sbix") {
+            self.error(
+                IssueKind::MissingTable,
+                face_idx,
+                None,
+                "Font contains no glyph outlines or bitmaps (no 'glyf', 'CFF ' or 'CFF2')".to_string(),
+            );
+        }
+        if has_glyf && !has(b"loca") {
+            self.error(IssueKind::MissingTable, face_idx, Some("loca"), "Table 'glyf' present without 'loca'".to_string());
+        }
+
+        let face = match Face::parse(data, index) {
+            Ok(face) => face,
+            Err(e) => {
+                self.error(IssueKind::Structure, face_idx, None, format!("Failed to parse font face: {}", e));
+                return;
+            }
+        };
+
+        self.check_glyph_data(&face, face_idx);
+        self.check_metrics(&face, face_idx);
+        self.check_cmap(&face, face_idx);
+        self.check_names(&face, face_idx);
+    }
+
+    // maxp.numGlyphs, loca and glyf must agree with each other
+    fn check_glyph_data(&mut self, face: &Face, face_idx: Option<u32>) {
+        let num_glyphs = face.number_of_glyphs() as usize;
+        let raw = face.raw_face();
+
+        if let (Some(loca), Some(glyf)) = (raw.table(Tag::from_bytes(b"loca")), raw.table(Tag::from_bytes(b"glyf"))) {
+            let long = matches!(face.tables().head.index_to_location_format, IndexToLocationFormat::Long);
+            let entry_size = if long { 4 } else { 2 };
+            let expected_len = (num_glyphs + 1) * entry_size;
+
+            if loca.len() < expected_len {
+                self.error(
+                    IssueKind::GlyphData,
+                    face_idx,
+                    Some("loca"),
+                    format!(
+                        "'loca' has {} entries but 'maxp' declares {} glyphs",
+                        loca.len() / entry_size,
+                        num_glyphs
+                    ),
+                );
+            } else {
+                let offset_at = |i: usize| {
+                    if long {
+                        read_u32(loca, i * 4).map(|v| v as usize)
+                    } else {
+                        read_u16(loca, i * 2).map(|v| v as usize * 2)
+                    }
+                };
+
+                let mut prev = 0;
+                for i in 0..=num_glyphs {
+                    let offset = offset_at(i).unwrap_or(0);
+                    if offset < prev {
+                        self.error(
+                            IssueKind::GlyphData,
+                            face_idx,
+                            Some("loca"),
+                            format!("'loca' offsets are not ascending at glyph {}", i),
+                        );
+                        break;
+                    }
+                    if offset > glyf.len() {
+                        self.error(
+                            IssueKind::GlyphData,
+                            face_idx,
+                            Some("glyf"),
+                            format!("Glyph {} points past the end of 'glyf'", i.saturating_sub(1)),
+                        );
+                        break;
+                    }
+                    prev = offset;
+                }
+            }
+        }
+
+        if let Some(hmtx) = raw.table(Tag::from_bytes(b"hmtx")) {
+            let metrics = face.tables().hhea.number_of_metrics as usize;
+            if metrics == 0 || metrics > num_glyphs {
+                self.error(
+                    IssueKind::Metrics,
+                    face_idx,
+                    Some("hhea"),
+                    format!("'hhea' numberOfHMetrics is {} for {} glyphs", metrics, num_glyphs),
+                );
+            } else if hmtx.len() < metrics * 4 + (num_glyphs - metrics) * 2 {
+                self.error(
+                    IssueKind::Metrics,
+                    face_idx,
+                    Some("hmtx"),
+                    "'hmtx' is shorter than 'hhea' and 'maxp' require".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_metrics(&mut self, face: &Face, face_idx: Option<u32>) {
+        let upem = face.units_per_em();
+        if !(16..=16384).contains(&upem) {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("head"),
+                format!("unitsPerEm {} is outside the valid range 16..16384", upem),
+            );
+        }
+
+        let hhea = face.tables().hhea;
+        if hhea.ascender <= 0 {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("hhea"),
+                format!("'hhea' ascender is not positive ({})", hhea.ascender),
+            );
+        }
+        if hhea.descender > 0 {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("hhea"),
+                format!("'hhea' descender is positive ({})", hhea.descender),
+            );
+        }
+
+        let os2_data = match face.raw_face().table(Tag::from_bytes(b"OS/2")) {
+            Some(d) => d,
+            None => return,
+        };
+        let os2 = match face.tables().os2 {
+            Some(t) => t,
+            None => {
+                self.error(IssueKind::Structure, face_idx, Some("OS/2"), "'OS/2' table is malformed".to_string());
+                return;
+            }
+        };
+
+        let weight_class = read_u16(os2_data, 4).unwrap_or(0);
+        if !(1..=1000).contains(&weight_class) {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("OS/2"),
+                format!("usWeightClass {} is outside 1..1000", weight_class),
+            );
+        }
+        let width_class = read_u16(os2_data, 6).unwrap_or(0);
+        if !(1..=9).contains(&width_class) {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("OS/2"),
+                format!("usWidthClass {} is outside 1..9", width_class),
+            );
+        }
+
+        if os2.typographic_ascender() <= 0 || os2.typographic_descender() > 0 {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("OS/2"),
+                format!(
+                    "Typographic ascender/descender look inverted ({} / {})",
+                    os2.typographic_ascender(),
+                    os2.typographic_descender()
+                ),
+            );
+        }
+
+        let win_ascent = os2.windows_ascender();
+        let win_descent = os2.windows_descender();
+        if win_ascent == 0 && win_descent == 0 {
+            self.warning(
+                IssueKind::Metrics,
+                face_idx,
+                Some("OS/2"),
+                "usWinAscent and usWinDescent are both zero; text will be clipped on Windows".to_string(),
+            );
+        }
+    }
+
+    fn check_cmap(&mut self, face: &Face, face_idx: Option<u32>) {
+        let cmap = match face.tables().cmap {
+            Some(cmap) => cmap,
+            None => {
+                if face.raw_face().table(Tag::from_bytes(b"cmap")).is_some() {
+                    self.error(IssueKind::Cmap, face_idx, Some("cmap"), "'cmap' table is malformed".to_string());
+                }
+                return;
+            }
+        };
+
+        // Windows needs either a Unicode mapping or the (3, 0) symbol encoding
+        let usable = cmap
+            .subtables
+            .into_iter()
+            .any(|s| s.is_unicode() || (s.platform_id == PlatformId::Windows && s.encoding_id == 0));
+        if !usable {
+            self.error(
+                IssueKind::Cmap,
+                face_idx,
+                Some("cmap"),
+                "'cmap' has no Unicode or Windows symbol subtable".to_string(),
+            );
+            return;
+        }
+
+        let num_glyphs = u32::from(face.number_of_glyphs());
+        let raw_cmap = face.raw_face().table(Tag::from_bytes(b"cmap")).unwrap_or_default();
+        let mut out_of_range = false;
+        for index in 0..cmap.subtables.len() {
+            let subtable = match cmap.subtables.get(index) {
+                Some(s) if s.is_unicode() => s,
+                _ => continue,
+            };
+            // Encoding records are 8 bytes after the 4-byte header, with the offset last
+            let highest = read_u32(raw_cmap, 4 + usize::from(index) * 8 + 4)
+                .and_then(|offset| raw_cmap.get(offset as usize..))
+                .and_then(highest_glyph);
+            match highest {
+                Some(highest) => out_of_range |= highest >= num_glyphs,
+                // Other formats store a glyph per codepoint, so walking them costs no more than reading them
+                None => subtable.codepoints(|cp| {
+                    if subtable.glyph_index(cp).is_some_and(|gid| u32::from(gid.0) >= num_glyphs) {
+                        out_of_range = true;
+                    }
+                }),
+            }
+        }
+        if out_of_range {
+            self.error(
+                IssueKind::Cmap,
+                face_idx,
+                Some("cmap"),
+                "'cmap' maps characters to glyphs that do not exist".to_string(),
+            );
+        }
+    }
+
+    fn check_names(&mut self, face: &Face, face_idx: Option<u32>) {
+        let names = face.names();
+        if names.is_empty() {
+            if face.raw_face().table(Tag::from_bytes(b"name")).is_some() {
+                self.error(IssueKind::Name, face_idx, Some("name"), "'name' table contains no records".to_string());
+            }
+            return;
+        }
+
+        let has_id = |id: u16| names.into_iter().any(|n| n.name_id == id);
+        if !has_id(1) {
+            self.error(IssueKind::Name, face_idx, Some("name"), "Font family name (ID 1) is missing".to_string());
+        }
+        for (id, label) in [(2, "subfamily"), (4, "full name"), (6, "PostScript name")] {
+            if !has_id(id) {
+                self.warning(
+                    IssueKind::Name,
+                    face_idx,
+                    Some("name"),
+                    format!("Name ID {} ({}) is missing", id, label),
+                );
+            }
+        }
+        if !names.into_iter().any(|n| n.platform_id == PlatformId::Windows) {
+            self.warning(
+                IssueKind::Name,
+                face_idx,
+                Some("name"),
+                "No Windows-platform name records; Windows may show the font under a different name".to_string(),
+            );
+        }
+    }
+}
+
+// Highest glyph a format 4, 12 or 13 cmap subtable maps to, taken from its segments or groups
+// instead of looking up every codepoint. None for other formats.
+fn highest_glyph(subtable: &[u8]) -> Option<u32> {
+    match read_u16(subtable, 0)? {
+        4 => {
+            let seg_count = usize::from(read_u16(subtable, 6)? / 2);
+            // endCode, startCode, idDelta and idRangeOffset arrays, one u16 per segment each
+            let at = |array: usize, segment: usize| read_u16(subtable, array + segment * 2);
+            let (ends, starts, deltas, range_offsets) = (14, 16 + seg_count * 2, 16 + seg_count * 4, 16 + seg_count * 6);
+            let mut highest = 0u32;
+            for segment in 0..seg_count {
+                let end = at(ends, segment)?;
+                let start = at(starts, segment)?;
+                let delta = at(deltas, segment)?;
+                let range_offset = at(range_offsets, segment)?;
+                if start > end || (start == 0xFFFF && end == 0xFFFF) || range_offset == 0xFFFF {
+                    continue;
+                }
+                if range_offset == 0 {
+                    let (low, high) = (start.wrapping_add(delta), end.wrapping_add(delta));
+                    // A segment that wraps past 0xFFFF reaches the highest glyph id there is
+                    highest = highest.max(if low > high { 0xFFFF } else { u32::from(high) });
+                } else {
+                    // idRangeOffset counts from its own position into glyphIdArray
+                    let base = range_offsets + segment * 2 + usize::from(range_offset);
+                    for code in 0..=usize::from(end - start) {
+                        match read_u16(subtable, base + code * 2) {
+                            Some(0) | None => {}
+                            Some(glyph) => highest = highest.max(u32::from(glyph.wrapping_add(delta))),
+                        }
+                    }
+                }
+            }
+            Some(highest)
+        }
+        format @ (12 | 13) => {
+            let count = read_u32(subtable, 12)? as usize;
+            let mut highest = 0u32;
+            // startCharCode, endCharCode and a glyph id per 12-byte group
+            for group in subtable.get(16..)?.chunks_exact(12).take(count) {
+                let (start, end, glyph) = (read_u32(group, 0)?, read_u32(group, 4)?, read_u32(group, 8)?);
+                if start > end {
+                    continue;
+                }
+                // Format 13 maps the whole group to one glyph
+                highest = highest.max(if format == 13 { glyph } else { glyph.saturating_add(end - start) });
+            }
+            Some(highest)
+        }
+        _ => None,
+    }
+}
+
+fn tag_name(tag: Tag) -> String {
+    String::from_utf8_lossy(&tag.to_bytes()).to_string()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// Sum of big-endian u32 words, zero padded. For 'head' the checkSumAdjustment field is skipped.
+fn table_checksum(data: &[u8], is_head: bool) -> u32 {
+    let mut sum = 0u32;
+    for (i, chunk) in data.chunks(4).enumerate() {
+        if is_head && i == 2 {
+            continue;
+        }
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum = sum.wrapping_add(u32::from_be_bytes(word));
+    }
+    sum
+}
+
+// Checksum of the whole file as the spec defines it: the 'head' table counts with its adjustment zeroed.
+fn file_checksum(data: &[u8], raw: &RawFace) -> u32 {
+    let mut sum = table_checksum(data, false);
+    if let Some(record) = raw
+        .table_records
+        .into_iter()
+        .find(|r| r.tag == Tag::from_bytes(b"head"))
+    {
+        if let Some(adjustment) = read_u32(data, record.offset as usize + 8) {
+            // Only correct if the table is 4-byte aligned, which the spec requires anyway
+            sum = sum.wrapping_sub(adjustment);
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be16(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    // Format 4 cmap mapping 'A' (and only 'A') to the given glyph
+    fn cmap_mapping_a_to(glyph: u16) -> Vec<u8> {
+        let mut cmap = be16(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(be16(&[4, 32, 0, 4, 4, 1, 0]));
+        cmap.extend(be16(&[0x41, 0xFFFF, 0, 0x41, 0xFFFF, glyph.wrapping_sub(0x41), 1, 0, 0]));
+        cmap
+    }
+
+    fn name_table() -> Vec<u8> {
+        let names = [(1, "Test"), (2, "Regular"), (4, "Test Regular"), (6, "Test-Regular")];
+        let mut records = Vec::new();
+        let mut strings = Vec::new();
+        for (id, text) in names {
+            let utf16: Vec<u16> = text.encode_utf16().collect();
+            records.extend(be16(&[3, 1, 0x409, id, utf16.len() as u16 * 2, strings.len() as u16]));
+            strings.extend(be16(&utf16));
+        }
+        let mut name = be16(&[0, names.len() as u16, 6 + records.len() as u16]);
+        name.extend(records);
+        name.extend(strings);
+        name
+    }
+
+    // A two-glyph TrueType font with empty outlines that passes every check
+    fn tables() -> Vec<([u8; 4], Vec<u8>)> {
+        let mut head = vec![0u8; 54];
+        head[..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+
+        let mut hhea = vec![0u8; 36];
+        hhea[..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        hhea[4..8].copy_from_slice(&be16(&[800, (-200i16) as u16]));
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+
+        let mut os2 = vec![0u8; 78];
+        os2[4..8].copy_from_slice(&be16(&[400, 5]));
+        os2[68..72].copy_from_slice(&be16(&[800, (-200i16) as u16]));
+        os2[74..78].copy_from_slice(&be16(&[1000, 200]));
+
+        let mut post = vec![0u8; 32];
+        post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+
+        vec![
+            (*b"OS/2", os2),
+            (*b"cmap", cmap_mapping_a_to(1)),
+            (*b"glyf", vec![0; 4]),
+            (*b"head", head),
+            (*b"hhea", hhea),
+            (*b"hmtx", vec![0; 8]),
+            (*b"loca", be16(&[0, 0, 0])),
+            (*b"maxp", [0x0000_5000u32.to_be_bytes().as_slice(), &2u16.to_be_bytes()].concat()),
+            (*b"name", name_table()),
+            (*b"post", post),
+        ]
+    }
+
+    fn replace(mut tables: Vec<([u8; 4], Vec<u8>)>, tag: &[u8; 4], data: Vec<u8>) -> Vec<([u8; 4], Vec<u8>)> {
+        tables.iter_mut().find(|(t, _)| t == tag).unwrap().1 = data;
+        tables
+    }
+
+    // Lays the tables out 4-byte aligned behind the directory, with correct checksums
+    fn build(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut font = be16(&[1, 0, tables.len() as u16, 0, 0, 0]);
+        let mut offset = 12 + tables.len() * 16;
+        for (tag, data) in tables {
+            font.extend(tag);
+            font.extend(table_checksum(data, tag == b"head").to_be_bytes());
+            font.extend((offset as u32).to_be_bytes());
+            font.extend((data.len() as u32).to_be_bytes());
+            offset += data.len().div_ceil(4) * 4;
+        }
+        for (_, data) in tables {
+            font.extend(data);
+            font.resize(font.len().div_ceil(4) * 4, 0);
+        }
+        let head = read_u32(&font, 12 + 3 * 16 + 8).unwrap() as usize;
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(table_checksum(&font, false));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+        font
+    }
+
+    fn kinds(report: &ValidationReport, severity: IssueSeverity) -> Vec<(IssueKind, Option<String>)> {
+        report
+            .issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .map(|i| (i.kind, i.table.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_font_has_no_issues() {
+        let report = validate_font_data("test.ttf", &build(&tables()));
+        assert_eq!(report.face_count, 1);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn truncated_file_reports_tables_out_of_bounds() {
+        let font = build(&tables());
+        // 'post' is laid out last
+        let report = validate_font_data("test.ttf", &font[..font.len() - 8]);
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::TableOutOfBounds, Some("post".to_string()))]);
+
+        let report = validate_font_data("test.ttf", &font[..20]);
+        assert!(report.has_errors());
+        assert!(report.errors().all(|i| i.kind == IssueKind::Structure));
+    }
+
+    #[test]
+    fn edited_table_fails_its_checksum() {
+        let mut font = build(&tables());
+        let last = font.len() - 1;
+        font[last - 4] ^= 0xFF;
+        let report = validate_font_data("test.ttf", &font);
+        assert!(!report.has_errors());
+        assert_eq!(
+            kinds(&report, IssueSeverity::Warning),
+            [
+                (IssueKind::Checksum, Some("post".to_string())),
+                (IssueKind::Checksum, Some("head".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn loca_must_cover_every_glyph_in_order() {
+        let short = replace(tables(), b"loca", be16(&[0, 0]));
+        let report = validate_font_data("test.ttf", &build(&short));
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::GlyphData, Some("loca".to_string()))]);
+
+        let descending = replace(tables(), b"loca", be16(&[0, 2, 1]));
+        let report = validate_font_data("test.ttf", &build(&descending));
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::GlyphData, Some("loca".to_string()))]);
+
+        let past_end = replace(tables(), b"loca", be16(&[0, 0, 4]));
+        let report = validate_font_data("test.ttf", &build(&past_end));
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::GlyphData, Some("glyf".to_string()))]);
+    }
+
+    #[test]
+    fn cmap_must_map_to_existing_glyphs() {
+        let report = validate_font_data("test.ttf", &build(&replace(tables(), b"cmap", cmap_mapping_a_to(2))));
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::Cmap, Some("cmap".to_string()))]);
+    }
+
+    #[test]
+    fn cmap_needs_a_unicode_or_symbol_subtable() {
+        let mut mac_only = cmap_mapping_a_to(1);
+        mac_only[4..8].copy_from_slice(&be16(&[1, 0]));
+        let report = validate_font_data("test.ttf", &build(&replace(tables(), b"cmap", mac_only)));
+        assert_eq!(kinds(&report, IssueSeverity::Error), [(IssueKind::Cmap, Some("cmap".to_string()))]);
+    }
+
+    #[test]
+    fn highest_glyph_reads_format_4_segments() {
+        let cmap = cmap_mapping_a_to(7);
+        assert_eq!(highest_glyph(&cmap[12..]), Some(7));
+
+        // A delta that wraps inside the segment reaches glyph 0xFFFF
+        let wrapping = [be16(&[4, 24, 0, 2, 2, 0, 0]), be16(&[0x20, 0, 0x10, 0xFFE0, 0])].concat();
+        assert_eq!(highest_glyph(&wrapping), Some(0xFFFF));
+
+        // Segment 'A'..'B' read through glyphIdArray, with a delta added to non-zero entries
+        let indexed = [be16(&[4, 28, 0, 2, 2, 0, 0]), be16(&[0x42, 0, 0x41, 3, 2, 9, 0])].concat();
+        assert_eq!(highest_glyph(&indexed), Some(12));
+    }
+
+    #[test]
+    fn highest_glyph_reads_format_12_and_13_groups() {
+        let groups = |format: u16| {
+            let mut table = be16(&[format, 0, 0, 40, 0, 0, 0, 2]);
+            for value in [0x20u32, 0x7E, 3, 0x4E00, 0x10_FFFF, 100] {
+                table.extend(value.to_be_bytes());
+            }
+            table
+        };
+        assert_eq!(highest_glyph(&groups(12)), Some(100 + 0x10_FFFF - 0x4E00));
+        assert_eq!(highest_glyph(&groups(13)), Some(100));
+        assert_eq!(highest_glyph(&be16(&[6, 10, 0, 0x20, 0])), None);
+    }
+}
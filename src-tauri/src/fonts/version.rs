@@ -0,0 +1,130 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use ttf_parser::{Face, Tag};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FontVersion {
+    // head.fontRevision, a 16.16 fixed point number
+    pub revision: f64,
+    // Name ID 5, e.g. "Version 2.137;hotconv 1.0.109"
+    pub version_string: Option<String>,
+}
+
+impl FontVersion {
+    pub fn compare(&self, other: &FontVersion) -> Ordering {
+        // Vendors rarely use more than three decimals; compare on that grid to absorb fixed point noise
+        let a = (self.revision * 1000.0).round() as i64;
+        let b = (other.revision * 1000.0).round() as i64;
+        let by_revision = a.cmp(&b);
+        // Some fonts leave fontRevision at 0; only the version string can tell then
+        if by_revision != Ordering::Equal && a != 0 && b != 0 {
+            return by_revision;
+        }
+
+        match (
+            self.version_string.as_deref().map(version_components),
+            other.version_string.as_deref().map(version_components),
+        ) {
+            (Some(a), Some(b)) => a.cmp(&b).then(by_revision),
+            _ => by_revision,
+        }
+    }
+}
+
+pub fn read_font_version(data: &[u8], index: u32) -> Option<FontVersion> {
+    let face = Face::parse(data, index).ok()?;
+    let head = face.raw_face().table(Tag::from_bytes(b"head"))?;
+    let fixed = i32::from_be_bytes(head.get(4..8)?.try_into().ok()?);
+
+    let version_string = face
+        .names()
+        .into_iter()
+        .filter(|n| n.name_id == 5 && n.is_unicode())
+        .find_map(|n| n.to_string());
+
+    Some(FontVersion {
+        revision: fixed as f64 / 65536.0,
+        version_string,
+    })
+}
+
+pub fn read_font_version_file(path: &Path) -> Option<FontVersion> {
+    let data = fs::read(path).ok()?;
+    read_font_version(&data, 0)
+}
+
+// Digits of the minor version that are compared; the rest are below any real difference
+const MINOR_DIGITS: usize = 9;
+
+// "Version 2.137;hotconv 1.0" -> [2, 137000000]. The minor version is a decimal fraction, so
+// 1.01 and 1.010 are the same version; any further parts count as plain integers.
+fn version_components(s: &str) -> Vec<u32> {
+    let lower = s.to_lowercase();
+    let rest = lower.strip_prefix("version").unwrap_or(&lower).trim_start();
+    rest.split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .enumerate()
+        .map(|(i, part)| match i {
+            1 => part.chars().chain(std::iter::repeat('0')).take(MINOR_DIGITS).collect(),
+            _ => part.to_string(),
+        })
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(revision: f64, version_string: Option<&str>) -> FontVersion {
+        FontVersion {
+            revision,
+            version_string: version_string.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn trailing_zeros_in_the_minor_version_do_not_count() {
+        assert_eq!(version_components("Version 1.01"), version_components("Version 1.010"));
+        let a = version(1.01, Some("Version 1.01"));
+        let b = version(1.010, Some("Version 1.010"));
+        assert_eq!(a.compare(&b), Ordering::Equal);
+        assert_eq!(version_components("Version 1.2").cmp(&version_components("Version 1.10")), Ordering::Greater);
+    }
+
+    #[test]
+    fn version_string_stops_at_the_first_separator() {
+        assert_eq!(version_components("Version 2.000;hotconv 1.0.109"), vec![2, 0]);
+        assert_eq!(version_components("2.137 build 5"), vec![2, 137_000_000]);
+        assert_eq!(version_components("Version 1.000.2"), vec![1, 0, 2]);
+        let a = version(2.0, Some("Version 2.000;hotconv 1.0.109"));
+        let b = version(2.0, Some("Version 2.000;hotconv 1.0.88"));
+        assert_eq!(a.compare(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn revision_decides_before_the_version_string() {
+        let a = version(2.0, Some("Version 1.500"));
+        let b = version(1.5, Some("Version 2.000"));
+        assert_eq!(a.compare(&b), Ordering::Greater);
+        // Below the three-decimal grid
+        assert_eq!(version(1.0, None).compare(&version(1.0001, None)), Ordering::Equal);
+    }
+
+    #[test]
+    fn missing_revision_falls_back_to_the_version_string() {
+        let unset = version(0.0, Some("Version 3.000"));
+        let set = version(1.5, Some("Version 1.500"));
+        assert_eq!(unset.compare(&set), Ordering::Greater);
+        assert_eq!(set.compare(&unset), Ordering::Less);
+        assert_eq!(version(0.0, Some("Version 1.1")).compare(&version(0.0, Some("Version 1.05"))), Ordering::Greater);
+        // Nothing else to go on
+        assert_eq!(version(0.0, None).compare(&set), Ordering::Less);
+        assert_eq!(version(0.0, None).compare(&version(0.0, Some("Version 2.0"))), Ordering::Equal);
+    }
+}
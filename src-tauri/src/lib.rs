@@ -7,16 +7,68 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+// Set when this process was relaunched elevated to install fonts for all users
+pub fn run_elevated_helper() -> Option<i32> {
+    fonts::elevation::run_elevated_helper()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .manage(fonts::install::InstallTasks::default())
+        .manage(fonts::enumerate::VariantCache::default())
+        .setup(|app| {
+            // Font files that were still in use when uninstalled last time
+            let _ = fonts::pending::process_pending_deletions(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
+            fonts::activation::list_activation_sets,
+            fonts::activation::create_activation_set,
+            fonts::activation::add_fonts_to_set,
+            fonts::activation::set_activation_set_active,
+            fonts::activation::delete_activation_set,
+            fonts::activation::list_active_fonts,
             fonts::enumerate::list_fonts,
+            fonts::search::search_fonts,
+            fonts::similar::find_similar_fonts,
+            fonts::metrics::get_font_metrics,
+            fonts::licence::licence_report,
+            fonts::inventory::export_inventory,
+            fonts::inventory::diff_inventories,
+            fonts::backup::backup_user_fonts,
+            fonts::backup::restore_backup,
+            fonts::duplicates::find_duplicate_fonts,
             fonts::install::install_fonts,
+            fonts::install::execute_install_plan,
+            fonts::install::cancel_install,
+            fonts::organize::get_organizer,
+            fonts::organize::set_font_tags,
+            fonts::organize::rename_tag,
+            fonts::organize::delete_tag,
+            fonts::organize::set_favourite,
+            fonts::organize::create_collection,
+            fonts::organize::rename_collection,
+            fonts::organize::delete_collection,
+            fonts::organize::add_to_collection,
+            fonts::organize::remove_from_collection,
+            fonts::pending::list_pending_deletions,
+            fonts::pending::retry_pending_deletions,
+            fonts::plan::plan_install,
+            fonts::library::import_to_library,
+            fonts::library::list_library,
+            fonts::library::remove_from_library,
             fonts::preview::read_font_file_base64,
-            fonts::uninstall::uninstall_font
+            fonts::quarantine::list_quarantine,
+            fonts::quarantine::restore_quarantined_fonts,
+            fonts::quarantine::purge_quarantine,
+            fonts::repair::scan_font_registry,
+            fonts::repair::repair_font_registry,
+            fonts::uninstall::uninstall_font,
+            fonts::uninstall::uninstall_fonts,
+            fonts::validate::validate_font
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // Elevated install helper: does its work and exits without opening a window
+    if let Some(code) = font_manager_lib::run_elevated_helper() {
+        std::process::exit(code);
+    }
     font_manager_lib::run()
 }
@@ -1 +1,137 @@
 pub mod registry;
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr::null_mut;
+use winapi::shared::minwindef::DWORD;
+use winapi::shared::sddl::ConvertSidToStringSidW;
+use winapi::shared::winerror::ERROR_SUCCESS;
+use winapi::um::accctrl::SE_FILE_OBJECT;
+use winapi::um::aclapi::GetNamedSecurityInfoW;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetCurrentProcess, GetExitCodeProcess, OpenProcessToken};
+use winapi::um::securitybaseapi::GetTokenInformation;
+use winapi::um::shellapi::{ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::winbase::{LocalFree, MoveFileExW, INFINITE, MOVEFILE_DELAY_UNTIL_REBOOT};
+use winapi::um::winnt::{
+    TokenElevation, HANDLE, LPWSTR, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID, TOKEN_ELEVATION,
+    TOKEN_QUERY,
+};
+use winapi::um::winuser::{SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, SW_HIDE, WM_FONTCHANGE};
+
+// NT SERVICE\TrustedInstaller, the owner of files that ship with Windows
+const TRUSTED_INSTALLER_SID: &str = "S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464";
+
+// Tell running applications that the set of installed fonts changed
+pub fn broadcast_font_change() {
+    unsafe {
+        SendMessageTimeoutW(
+            HWND_BROADCAST,
+            WM_FONTCHANGE,
+            0,
+            0,
+            SMTO_ABORTIFHUNG,
+            1000,
+            std::ptr::null_mut(),
+        );
+    }
+}
+
+// Asks Windows to delete the file during the next boot. Needs write access to HKLM, so this
+// usually only succeeds when running elevated.
+pub fn delete_on_reboot(path: &Path) -> bool {
+    let wide = to_wide(path);
+    unsafe { MoveFileExW(wide.as_ptr(), std::ptr::null(), MOVEFILE_DELAY_UNTIL_REBOOT) != 0 }
+}
+
+// Files installed by Windows itself are owned by TrustedInstaller; anything an admin copied in is not
+pub fn is_owned_by_trusted_installer(path: &Path) -> bool {
+    let wide = to_wide(path);
+    unsafe {
+        let mut owner: PSID = null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+        let status = GetNamedSecurityInfoW(
+            wide.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION,
+            &mut owner,
+            null_mut(),
+            null_mut(),
+            null_mut(),
+            &mut descriptor,
+        );
+        if status != ERROR_SUCCESS {
+            return false;
+        }
+
+        let mut sid: LPWSTR = null_mut();
+        let mut trusted = false;
+        if ConvertSidToStringSidW(owner, &mut sid) != 0 {
+            let len = (0..).take_while(|&i| *sid.offset(i) != 0).count();
+            trusted = String::from_utf16_lossy(std::slice::from_raw_parts(sid, len)) == TRUSTED_INSTALLER_SID;
+            LocalFree(sid as _);
+        }
+        LocalFree(descriptor);
+        trusted
+    }
+}
+
+// Whether this process already runs with an elevated (admin) token
+pub fn is_elevated() -> bool {
+    unsafe {
+        let mut token: HANDLE = null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut size: DWORD = 0;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut _ as *mut _,
+            std::mem::size_of::<TOKEN_ELEVATION>() as DWORD,
+            &mut size,
+        );
+        CloseHandle(token);
+        ok != 0 && elevation.TokenIsElevated != 0
+    }
+}
+
+// Starts the program through the UAC prompt and waits for it to exit, returning its exit code.
+// Declining the prompt fails with ERROR_CANCELLED.
+pub fn run_elevated_and_wait(program: &Path, parameters: &str) -> io::Result<u32> {
+    let verb = to_wide("runas");
+    let file = to_wide(program);
+    let params = to_wide(parameters);
+    unsafe {
+        let mut info: SHELLEXECUTEINFOW = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as DWORD;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.lpParameters = params.as_ptr();
+        info.nShow = SW_HIDE;
+        if ShellExecuteExW(&mut info) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if info.hProcess.is_null() {
+            return Err(io::Error::other("Elevated process did not start"));
+        }
+
+        WaitForSingleObject(info.hProcess, INFINITE);
+        let mut code: DWORD = 0;
+        let ok = GetExitCodeProcess(info.hProcess, &mut code);
+        CloseHandle(info.hProcess);
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(code)
+    }
+}
+
+fn to_wide(s: impl AsRef<OsStr>) -> Vec<u16> {
+    s.as_ref().encode_wide().chain(std::iter::once(0)).collect()
+}
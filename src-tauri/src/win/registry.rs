@@ -1,42 +1,94 @@
+use serde::{Deserialize, Serialize};
 use std::io;
 use winreg::enums::*;
 use winreg::RegKey;
 
-pub fn register_font(font_name: &str, file_name: &str) -> io::Result<()> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let path = r"Software\Microsoft\Windows NT\CurrentVersion\Fonts";
-    let (key, _) = hkcu.create_subkey(path)?;
+const FONTS_KEY: &str = r"Software\Microsoft\Windows NT\CurrentVersion\Fonts";
+
+// HKCU holds per-user installs, HKLM the fonts every user gets
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum FontHive {
+    CurrentUser,
+    LocalMachine,
+}
+
+impl FontHive {
+    fn root(self) -> RegKey {
+        match self {
+            FontHive::CurrentUser => RegKey::predef(HKEY_CURRENT_USER),
+            FontHive::LocalMachine => RegKey::predef(HKEY_LOCAL_MACHINE),
+        }
+    }
+}
+
+pub fn register_font(hive: FontHive, font_name: &str, file_name: &str) -> io::Result<()> {
+    let (key, _) = hive.root().create_subkey(FONTS_KEY)?;
     
     // The value name is the font name (e.g., "Arial (TrueType)"), and the data is the file name
     key.set_value(font_name, &file_name)?;
     Ok(())
 }
 
-pub fn remove_font_registry_entry(target_path: &str) -> io::Result<bool> {
+// Removes every per-user value pointing at the file and returns the removed (name, data) pairs
+pub fn remove_font_registry_entry(target_path: &str) -> io::Result<Vec<(String, String)>> {
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let path = r"Software\Microsoft\Windows NT\CurrentVersion\Fonts";
-    let key = hkcu.open_subkey_with_flags(path, KEY_READ | KEY_WRITE)?;
+    let key = hkcu.open_subkey_with_flags(FONTS_KEY, KEY_READ | KEY_WRITE)?;
     
-    let mut found = false;
     let target_lower = target_path.to_lowercase();
     
     // We need to collect keys to delete first to avoid modifying while iterating
-    let mut keys_to_delete = Vec::new();
+    let mut values_to_delete = Vec::new();
 
     for i in key.enum_values() {
         if let Ok((name, value)) = i {
             // Font registry values are typically REG_SZ containing the file path or filename
             let val_str = value.to_string(); 
             if val_str.to_lowercase() == target_lower {
-                keys_to_delete.push(name);
+                values_to_delete.push((name, val_str));
             }
         }
     }
 
-    for name in keys_to_delete {
-        key.delete_value(&name)?;
-        found = true;
+    for (name, _) in &values_to_delete {
+        key.delete_value(name)?;
     }
     
-    Ok(found)
+    Ok(values_to_delete)
+}
+
+// Current data of a font value, None if the value doesn't exist
+pub fn get_font_registry_value(hive: FontHive, font_name: &str) -> io::Result<Option<String>> {
+    let key = match hive.root().open_subkey(FONTS_KEY) {
+        Ok(key) => key,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    match key.get_value::<String, _>(font_name) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn delete_font_registry_value(hive: FontHive, font_name: &str) -> io::Result<()> {
+    let key = hive.root().open_subkey_with_flags(FONTS_KEY, KEY_READ | KEY_WRITE)?;
+    match key.delete_value(font_name) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Every (value name, data) pair under the hive's Fonts key; a missing key reads as empty
+pub fn list_font_registry_values(hive: FontHive) -> io::Result<Vec<(String, String)>> {
+    let key = match hive.root().open_subkey(FONTS_KEY) {
+        Ok(key) => key,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(key
+        .enum_values()
+        .filter_map(|v| v.ok())
+        .map(|(name, value)| (name, value.to_string()))
+        .collect())
 }
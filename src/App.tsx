@@ -218,14 +218,23 @@ function App() {
               <div className="text-center">
                 <h3 className="text-lg font-medium">安装完成</h3>
                 <p className="text-muted-foreground mt-2">
-                  成功: {installResult.successCount} | 失败: {installResult.failedCount}
+                  成功: {installResult.successCount} | 跳过: {installResult.skippedCount} | 失败: {installResult.failedCount}
                 </p>
               </div>
               {installResult.errors.length > 0 && (
                 <ScrollArea className="h-40 w-full rounded border p-2 bg-muted/50 text-sm">
                   {installResult.errors.map((err, i) => (
                     <p key={i} className="text-destructive mb-1">
-                      {err}
+                      {err.message}
+                    </p>
+                  ))}
+                </ScrollArea>
+              )}
+              {installResult.warnings.length > 0 && (
+                <ScrollArea className="h-32 w-full rounded border p-2 bg-muted/50 text-sm">
+                  {installResult.warnings.map((warning, i) => (
+                    <p key={i} className="text-muted-foreground mb-1">
+                      {warning.path}: {warning.message}
                     </p>
                   ))}
                 </ScrollArea>
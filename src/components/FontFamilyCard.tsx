@@ -162,9 +162,9 @@ export const FontFamilyCard = memo(function FontFamilyCard({
                       animation: variantsOpen ? `slideIn 0.3s ease-out ${index * 0.05}s backwards` : 'none',
                     }}
                     onClick={() => onPreview(variant)}
-                    onPointerEnter={() => prefetchFontPreviewSrc(variant.path)}
-                    onFocus={() => prefetchFontPreviewSrc(variant.path)}
-                    onPointerDown={() => prefetchFontPreviewSrc(variant.path)}
+                    onPointerEnter={() => prefetchFontPreviewSrc(variant)}
+                    onFocus={() => prefetchFontPreviewSrc(variant)}
+                    onPointerDown={() => prefetchFontPreviewSrc(variant)}
                     tabIndex={0}
                   >
                     <div className="flex items-center gap-2 min-w-0">
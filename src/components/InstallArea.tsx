@@ -39,7 +39,11 @@ export function InstallArea({ onInstallStart, onInstallComplete }: InstallAreaPr
             onInstallComplete({
                 successCount: 0,
                 failedCount: paths.length,
-                errors: [String(error)]
+                skippedCount: 0,
+                errors: [{ kind: "io", message: String(error) }],
+                warnings: [],
+                files: [],
+                cancelled: false
             });
         } finally {
             setIsInstalling(false);
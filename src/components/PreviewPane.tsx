@@ -65,7 +65,7 @@ function PreviewPaneContent({
 
     (async () => {
       try {
-        const src = await getFontPreviewSrc(selectedVariant.path);
+        const src = await getFontPreviewSrc(selectedVariant);
         
         if (cancelled) return;
 
@@ -1,4 +1,5 @@
 import { readFontFileBase64 } from "@/lib/tauri";
+import { FontVariant } from "@/types/fonts";
 
 const MAX_CACHE = 50;
 const cache = new Map<string, string>();
@@ -20,43 +21,46 @@ function evictIfNeeded() {
   }
 }
 
-export async function getFontPreviewSrc(path: string): Promise<string> {
-  const cached = cache.get(path);
+// Keyed by FontVariant.identity, so a font that moved or was reinstalled keeps its cached preview
+export async function getFontPreviewSrc(variant: Pick<FontVariant, "identity" | "path">): Promise<string> {
+  const key = variant.identity;
+  const cached = cache.get(key);
   if (cached) {
-    touch(path);
+    touch(key);
     return cached;
   }
 
-  const existing = inflight.get(path);
+  const existing = inflight.get(key);
   if (existing) return existing;
 
   const p = (async () => {
-    const { base64, mime } = await readFontFileBase64(path);
+    const { base64, mime } = await readFontFileBase64(variant.path);
     const src = `data:${mime};base64,${base64}`;
-    cache.set(path, src);
+    cache.set(key, src);
     evictIfNeeded();
     return src;
   })();
 
-  inflight.set(path, p);
+  inflight.set(key, p);
   try {
     return await p;
   } finally {
-    inflight.delete(path);
+    inflight.delete(key);
   }
 }
 
-export function prefetchFontPreviewSrc(path: string) {
-  if (cache.has(path) || inflight.has(path)) return;
+export function prefetchFontPreviewSrc(variant: Pick<FontVariant, "identity" | "path">) {
+  const key = variant.identity;
+  if (cache.has(key) || inflight.has(key)) return;
   if (typeof window === "undefined") return;
 
-  const existing = prefetchTimers.get(path);
+  const existing = prefetchTimers.get(key);
   if (existing) window.clearTimeout(existing);
 
   const id = window.setTimeout(() => {
-    prefetchTimers.delete(path);
-    void getFontPreviewSrc(path).catch(() => {});
+    prefetchTimers.delete(key);
+    void getFontPreviewSrc(variant).catch(() => {});
   }, 120);
 
-  prefetchTimers.set(path, id);
+  prefetchTimers.set(key, id);
 }
@@ -1,15 +1,479 @@
 import { invoke } from "@tauri-apps/api/core";
-import { ListFontsResult } from "@/types/fonts";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { EmbeddingLevel, FontClass, FontScope, FontVariant, LicenceInfo, ListFontsResult, NameVersionKey } from "@/types/fonts";
+
+export type FontErrorKind =
+  | "notFound"
+  | "unsupportedFormat"
+  | "parseFailed"
+  | "invalidFont"
+  | "accessDenied"
+  | "fileInUse"
+  | "registryFailure"
+  | "outsideAllowedDir"
+  | "tooLarge"
+  | "protectedSystemFont"
+  | "notRegistered"
+  | "alreadyExists"
+  | "invalidName"
+  | "io";
+
+export interface FontError {
+  kind: FontErrorKind;
+  path?: string;
+  osCode?: number;
+  message: string;
+}
+
+export type IssueSeverity = "error" | "warning";
+
+export type IssueKind =
+  | "structure"
+  | "tableOutOfBounds"
+  | "checksum"
+  | "missingTable"
+  | "glyphData"
+  | "metrics"
+  | "cmap"
+  | "name";
+
+export interface ValidationIssue {
+  severity: IssueSeverity;
+  kind: IssueKind;
+  path: string;
+  faceIndex?: number;
+  table?: string;
+  message: string;
+}
+
+export interface ValidationReport {
+  path: string;
+  faceCount: number;
+  issues: ValidationIssue[];
+}
+
+export type ConflictPolicy = "skip" | "overwrite" | "keepBoth" | "upgradeIfNewer";
+
+// "allUsers" installs into %SystemRoot%\Fonts and asks for administrator permission
+export type InstallScope = "currentUser" | "allUsers";
+
+export interface InstallOptions {
+  conflictPolicy?: ConflictPolicy;
+  atomic?: boolean;
+  scope?: InstallScope;
+}
+
+export type InstallDecision =
+  | "installed"
+  | "alreadyInstalled"
+  | "overwritten"
+  | "keptBoth"
+  | "upgraded"
+  | "skipped"
+  | "skippedNotNewer"
+  | "skippedConflict"
+  | "failed"
+  | "rolledBack"
+  | "cancelled";
+
+export type PlannedAction =
+  | "install"
+  | "skipIdentical"
+  | "overwrite"
+  | "keepBoth"
+  | "upgrade"
+  | "skip"
+  | "skipNotNewer"
+  | "conflict"
+  | "invalid";
+
+export interface PostscriptConflict {
+  postscriptName: string;
+  installedPath: string;
+}
+
+export interface PlannedInstall {
+  source: string;
+  action: PlannedAction;
+  destination?: string;
+  // What the destination held when planned; executing refuses the entry if that changed
+  destinationHash?: string;
+  registryName?: string;
+  family?: string;
+  postscriptNames: string[];
+  conflict?: PostscriptConflict;
+  message?: string;
+  error?: FontError;
+  warnings: ValidationIssue[];
+}
+
+export interface InstallPlan {
+  fontsDir: string;
+  conflictPolicy: ConflictPolicy;
+  atomic: boolean;
+  scope: InstallScope;
+  items: PlannedInstall[];
+}
+
+export interface InstallFileResult {
+  path: string;
+  decision: InstallDecision;
+  destination?: string;
+  message?: string;
+  error?: FontError;
+}
+
+export interface RollbackReport {
+  removedFiles: string[];
+  restoredFiles: string[];
+  removedRegistryValues: string[];
+  restoredRegistryValues: string[];
+  errors: FontError[];
+  complete: boolean;
+}
 
 export interface InstallResult {
   successCount: number;
   failedCount: number;
-  errors: string[];
+  skippedCount: number;
+  errors: FontError[];
+  warnings: ValidationIssue[];
+  files: InstallFileResult[];
+  rollback?: RollbackReport;
+  cancelled: boolean;
+}
+
+export type InstallStage =
+  | "started"
+  | "copied"
+  | "registered"
+  | "skipped"
+  | "failed"
+  | "rolledBack"
+  | "cancelled";
+
+export interface InstallProgress {
+  batchId?: string;
+  index: number;
+  total: number;
+  path: string;
+  stage: InstallStage;
+  message?: string;
 }
 
 export interface UninstallResult {
   success: boolean;
   message: string;
+  error?: FontError;
+  quarantineId?: string;
+  pendingDeletion?: PendingDeletion;
+}
+
+export interface PendingDeletion {
+  path: string;
+  originalPath: string;
+  hash: string;
+  scheduledAt: number;
+  rebootScheduled: boolean;
+}
+
+export interface UninstallFileResult {
+  path: string;
+  variants: string[];
+  alsoRemoved: string[];
+  success: boolean;
+  error?: FontError;
+  quarantineId?: string;
+  pendingDeletion?: PendingDeletion;
+}
+
+export interface BatchUninstallResult {
+  successCount: number;
+  failedCount: number;
+  files: UninstallFileResult[];
+  unknownIds: string[];
+}
+
+export interface QuarantinedRegistryValue {
+  name: string;
+  data: string;
+}
+
+export interface QuarantineEntry {
+  id: string;
+  originalPath: string;
+  fileName: string;
+  registryValues: QuarantinedRegistryValue[];
+  family?: string;
+  postscriptNames: string[];
+  size: number;
+  removedAt: number;
+}
+
+export interface QuarantineActionResult {
+  id: string;
+  success: boolean;
+  error?: FontError;
+}
+
+export type FontHive = "currentUser" | "localMachine";
+
+export type RegistryIssueKind = "missingFile" | "unregisteredFile" | "relativePath";
+
+export interface RegistryIssue {
+  kind: RegistryIssueKind;
+  hive: FontHive;
+  valueName?: string;
+  valueData?: string;
+  path: string;
+  repairable: boolean;
+  message: string;
+}
+
+export interface RegistryScanReport {
+  issues: RegistryIssue[];
+  scannedValues: number;
+  scannedFiles: number;
+}
+
+export interface RepairFailure {
+  issue: RegistryIssue;
+  error: FontError;
+}
+
+export interface RepairResult {
+  repaired: RegistryIssue[];
+  failed: RepairFailure[];
+}
+
+export interface Collection {
+  name: string;
+  fonts: string[];
+  createdAt: number;
+}
+
+// Keyed by FontVariant.identity
+export interface Organizer {
+  tags: Record<string, string[]>;
+  collections: Collection[];
+  favourites: string[];
+  // Looked up when a font's own identity isn't used above
+  nameVersions: Record<string, NameVersionKey>;
+}
+
+export interface FontFilter {
+  tag?: string;
+  collection?: string;
+  favouritesOnly?: boolean;
+}
+
+// Every set criterion has to match; list criteria need all their entries
+export interface SearchQuery {
+  text?: string;
+  weightMin?: number;
+  weightMax?: number;
+  style?: string;
+  // OS/2 width classes, 1 to 9
+  widthMin?: number;
+  widthMax?: number;
+  // Any of these
+  scopes?: FontScope[];
+  // Any of these
+  classes?: FontClass[];
+  monospace?: boolean;
+  variable?: boolean;
+  colour?: boolean;
+  scripts?: string[];
+  features?: string[];
+  vendor?: string;
+  tags?: string[];
+  includeLibrary?: boolean;
+  offset?: number;
+  // Defaults to 100
+  limit?: number;
+}
+
+export interface SearchResult {
+  variants: FontVariant[];
+  total: number;
+  offset: number;
+}
+
+export interface VerticalMetrics {
+  ascender: number;
+  // Negative below the baseline
+  descender: number;
+  lineGap: number;
+}
+
+export interface LineDecoration {
+  position: number;
+  thickness: number;
+}
+
+export type MetricsIssueKind =
+  | "clipping"
+  | "lineHeightMismatch"
+  | "typoHheaMismatch"
+  | "missingHeight"
+  | "decoration";
+
+export interface MetricsIssue {
+  kind: MetricsIssueKind;
+  message: string;
+}
+
+// Font units unless noted
+export interface FontMetrics {
+  path: string;
+  faceIndex: number;
+  unitsPerEm: number;
+  hhea: VerticalMetrics;
+  typo?: VerticalMetrics;
+  winAscent?: number;
+  winDescent?: number;
+  useTypoMetrics: boolean;
+  xHeight?: number;
+  capHeight?: number;
+  underline?: LineDecoration;
+  strikeout?: LineDecoration;
+  yMin: number;
+  yMax: number;
+  // In ems
+  windowsLineHeight: number;
+  macLineHeight: number;
+  issues: MetricsIssue[];
+}
+
+export interface LicenceReportEntry {
+  id: string;
+  family: string;
+  fullName?: string;
+  path: string;
+  licence: LicenceInfo;
+  pdfEmbeddable: boolean;
+  appEmbeddable: boolean;
+}
+
+export interface LicenceReport {
+  total: number;
+  pdfEmbeddable: number;
+  appEmbeddable: number;
+  // fsType forbids embedding outright
+  restricted: number;
+  // Embeddable only as bitmaps
+  bitmapOnly: number;
+  // No OS/2 table, or conflicting fsType bits
+  unknown: number;
+  withoutLicenceText: number;
+  // Most restrictive first
+  fonts: LicenceReportEntry[];
+}
+
+export type InventoryFormat = "csv" | "json";
+
+export interface InventoryEntry {
+  family: string;
+  fullName?: string;
+  postscriptName?: string;
+  style: string;
+  weight: number;
+  version?: string;
+  vendor?: string;
+  scope: FontScope;
+  registryValueName?: string;
+  path: string;
+  faceIndex: number;
+  size?: number;
+  // SHA-256 of the whole file
+  hash?: string;
+  embedding?: EmbeddingLevel;
+  noSubsetting: boolean;
+  bitmapOnly: boolean;
+  licenceUrl?: string;
+  classification: FontClass;
+  identity: string;
+}
+
+// Same PostScript name on both sides, different file or version
+export interface InventoryChange {
+  key: string;
+  before: InventoryEntry[];
+  after: InventoryEntry[];
+  hashChanged: boolean;
+  versionChanged: boolean;
+}
+
+export interface InventoryDiff {
+  added: InventoryEntry[];
+  removed: InventoryEntry[];
+  changed: InventoryChange[];
+  unchanged: number;
+}
+
+export interface BackupResult {
+  path: string;
+  fontCount: number;
+  // Files that couldn't be read; the archive holds everything else
+  errors: FontError[];
+}
+
+export interface RestoreResult {
+  install: InstallResult;
+  taggedFonts: number;
+  // Damaged or missing archive entries, and a failure to save the tags
+  errors: FontError[];
+}
+
+export interface SimilarFont {
+  variant: FontVariant;
+  // 0 to 1, higher is closer
+  similarity: number;
+}
+
+export interface LibraryFile {
+  hash: string;
+  path: string;
+  fileName: string;
+  family: string;
+  postscriptNames: string[];
+}
+
+export interface CatalogEntry extends LibraryFile {
+  sourcePath: string;
+  size: number;
+  importedAt: number;
+  faces: FontVariant[];
+}
+
+export interface LibraryImportResult {
+  imported: CatalogEntry[];
+  errors: FontError[];
+}
+
+export interface LibraryRemoveResult {
+  hash: string;
+  success: boolean;
+  error?: FontError;
+}
+
+export interface ActivationSet {
+  name: string;
+  fonts: LibraryFile[];
+  active: boolean;
+  createdAt: number;
+}
+
+export interface ActivationResult {
+  set: ActivationSet;
+  errors: FontError[];
+}
+
+export interface ActiveFont {
+  path: string;
+  registryValueName: string;
+  family?: string;
+  sets: string[];
 }
 
 export interface FontFileBase64Result {
@@ -17,18 +481,229 @@ export interface FontFileBase64Result {
   mime: string;
 }
 
-export async function listFonts(): Promise<ListFontsResult> {
-  return await invoke("list_fonts");
+export type FontLocation = "system" | "user" | "other";
+
+export type DuplicateKind = "content" | "postscriptName" | "familyStyle";
+
+export interface DuplicateCopy {
+  path: string;
+  faceIndex: number;
+  postscriptName: string;
+  family: string;
+  style: string;
+  weight: number;
+  location: FontLocation;
+  hash?: string;
+  // Missing for files Windows only finds by scanning the fonts folders
+  registryHive?: FontHive;
+  isActive: boolean;
+  recommendRemoval: boolean;
+}
+
+export interface DuplicateGroup {
+  kind: DuplicateKind;
+  key: string;
+  identical: boolean;
+  copies: DuplicateCopy[];
+}
+
+export interface DuplicateReport {
+  groups: DuplicateGroup[];
+  recommendedRemovals: string[];
+}
+
+export async function getOrganizer(): Promise<Organizer> {
+  return await invoke("get_organizer");
+}
+
+export async function setFontTags(identity: string, tags: string[]): Promise<Organizer> {
+  return await invoke("set_font_tags", { identity, tags });
+}
+
+export async function renameTag(from: string, to: string): Promise<Organizer> {
+  return await invoke("rename_tag", { from, to });
+}
+
+export async function deleteTag(tag: string): Promise<Organizer> {
+  return await invoke("delete_tag", { tag });
+}
+
+export async function setFavourite(identity: string, favourite: boolean): Promise<Organizer> {
+  return await invoke("set_favourite", { identity, favourite });
 }
 
-export async function installFonts(paths: string[]): Promise<InstallResult> {
-  return await invoke("install_fonts", { paths });
+export async function createCollection(name: string): Promise<Organizer> {
+  return await invoke("create_collection", { name });
+}
+
+export async function renameCollection(from: string, to: string): Promise<Organizer> {
+  return await invoke("rename_collection", { from, to });
+}
+
+export async function deleteCollection(name: string): Promise<Organizer> {
+  return await invoke("delete_collection", { name });
+}
+
+export async function addToCollection(name: string, identities: string[]): Promise<Organizer> {
+  return await invoke("add_to_collection", { name, identities });
+}
+
+export async function removeFromCollection(name: string, identities: string[]): Promise<Organizer> {
+  return await invoke("remove_from_collection", { name, identities });
+}
+
+export async function importToLibrary(paths: string[]): Promise<LibraryImportResult> {
+  return await invoke("import_to_library", { paths });
+}
+
+export async function listLibrary(): Promise<CatalogEntry[]> {
+  return await invoke("list_library");
+}
+
+export async function removeFromLibrary(hashes: string[]): Promise<LibraryRemoveResult[]> {
+  return await invoke("remove_from_library", { hashes });
+}
+
+export async function listActivationSets(): Promise<ActivationSet[]> {
+  return await invoke("list_activation_sets");
+}
+
+export async function createActivationSet(name: string, paths: string[]): Promise<ActivationResult> {
+  return await invoke("create_activation_set", { name, paths });
+}
+
+export async function addFontsToSet(name: string, paths: string[]): Promise<ActivationResult> {
+  return await invoke("add_fonts_to_set", { name, paths });
+}
+
+export async function setActivationSetActive(name: string, active: boolean): Promise<ActivationResult> {
+  return await invoke("set_activation_set_active", { name, active });
+}
+
+export async function deleteActivationSet(name: string): Promise<FontError[]> {
+  return await invoke("delete_activation_set", { name });
+}
+
+export async function listActiveFonts(): Promise<ActiveFont[]> {
+  return await invoke("list_active_fonts");
+}
+
+export async function listFonts(includeLibrary?: boolean, filter?: FontFilter): Promise<ListFontsResult> {
+  return await invoke("list_fonts", { includeLibrary, filter });
+}
+
+export async function searchFonts(query: SearchQuery): Promise<SearchResult> {
+  return await invoke("search_fonts", { query });
+}
+
+// Installed faces of other families, closest first; limit defaults to 20
+export async function findSimilarFonts(variantId: string, limit?: number): Promise<SimilarFont[]> {
+  return await invoke("find_similar_fonts", { variantId, limit });
+}
+
+// Resolves to the number of faces written
+export async function exportInventory(format: InventoryFormat, path: string): Promise<number> {
+  return await invoke("export_inventory", { format, path });
+}
+
+// Compares two exports (CSV or JSON), or one export against the fonts installed now
+export async function diffInventories(base: string, other?: string): Promise<InventoryDiff> {
+  return await invoke("diff_inventories", { base, other });
+}
+
+// Zips every per-user installed font with its registry value names and tags
+export async function backupUserFonts(path: string): Promise<BackupResult> {
+  return await invoke("backup_user_fonts", { path });
+}
+
+// Reinstalls a backup through the install pipeline; progress events use batchId like installFonts
+export async function restoreBackup(
+  path: string,
+  options?: InstallOptions,
+  batchId?: string
+): Promise<RestoreResult> {
+  return await invoke("restore_backup", { path, options, batchId });
+}
+
+export async function licenceReport(): Promise<LicenceReport> {
+  return await invoke("licence_report");
+}
+
+export async function getFontMetrics(path: string, faceIndex?: number): Promise<FontMetrics> {
+  return await invoke("get_font_metrics", { path, faceIndex });
+}
+
+export async function installFonts(
+  paths: string[],
+  options?: InstallOptions,
+  batchId?: string
+): Promise<InstallResult> {
+  return await invoke("install_fonts", { paths, options, batchId });
+}
+
+export async function planInstall(paths: string[], options?: InstallOptions): Promise<InstallPlan> {
+  return await invoke("plan_install", { paths, options });
+}
+
+export async function executeInstallPlan(plan: InstallPlan, batchId?: string): Promise<InstallResult> {
+  return await invoke("execute_install_plan", { plan, batchId });
+}
+
+export async function cancelInstall(batchId: string): Promise<boolean> {
+  return await invoke("cancel_install", { batchId });
+}
+
+export async function onInstallProgress(handler: (progress: InstallProgress) => void): Promise<UnlistenFn> {
+  return await listen<InstallProgress>("install-progress", (event) => handler(event.payload));
 }
 
 export async function uninstallFont(path: string): Promise<UninstallResult> {
   return await invoke("uninstall_font", { path });
 }
 
+export async function uninstallFonts(selection: {
+  variantIds?: string[];
+  family?: string;
+}): Promise<BatchUninstallResult> {
+  return await invoke("uninstall_fonts", { variantIds: selection.variantIds, family: selection.family });
+}
+
+export async function listPendingDeletions(): Promise<PendingDeletion[]> {
+  return await invoke("list_pending_deletions");
+}
+
+export async function retryPendingDeletions(): Promise<PendingDeletion[]> {
+  return await invoke("retry_pending_deletions");
+}
+
+export async function listQuarantine(): Promise<QuarantineEntry[]> {
+  return await invoke("list_quarantine");
+}
+
+export async function restoreQuarantinedFonts(ids: string[]): Promise<QuarantineActionResult[]> {
+  return await invoke("restore_quarantined_fonts", { ids });
+}
+
+export async function purgeQuarantine(ids?: string[]): Promise<QuarantineActionResult[]> {
+  return await invoke("purge_quarantine", { ids });
+}
+
+export async function scanFontRegistry(): Promise<RegistryScanReport> {
+  return await invoke("scan_font_registry");
+}
+
+export async function repairFontRegistry(issues?: RegistryIssue[]): Promise<RepairResult> {
+  return await invoke("repair_font_registry", { issues });
+}
+
 export async function readFontFileBase64(path: string): Promise<FontFileBase64Result> {
   return await invoke("read_font_file_base64", { path });
 }
+
+export async function validateFont(path: string): Promise<ValidationReport> {
+  return await invoke("validate_font", { path });
+}
+
+export async function findDuplicateFonts(): Promise<DuplicateReport> {
+  return await invoke("find_duplicate_fonts");
+}
@@ -2,15 +2,73 @@ export type FontWeight = number;
 
 export type FontStyle = string;
 
+export type FontScope = "systemCore" | "machineInstalled" | "perUser" | "unregistered" | "library";
+
+export type FontClass =
+  | "serif"
+  | "sans"
+  | "slab"
+  | "monospace"
+  | "script"
+  | "decorative"
+  | "symbol"
+  | "unknown";
+
 export interface FontVariant {
   id: string;
   family: string;
   style: FontStyle;
   weight: FontWeight;
   path: string;
+  faceIndex: number;
   postscriptName?: string;
   fullName?: string;
   isSystemCore?: boolean;
+  scope: FontScope;
+  registryValueName?: string;
+  installed: boolean;
+  libraryHash?: string;
+  // Content-derived ("c:<hash>"), unlike id which depends on the file path
+  identity: string;
+  // Secondary key: the same PostScript name and version is the same font even if its tables differ
+  nameVersion?: NameVersionKey;
+  classification: FontClass;
+  metadata: FaceMetadata;
+  licence: LicenceInfo;
+}
+
+export interface NameVersionKey {
+  postscriptName: string;
+  version: string;
+}
+
+// OS/2 fsType embedding levels
+export type EmbeddingLevel = "restricted" | "previewPrint" | "editable" | "installable";
+
+export interface LicenceInfo {
+  fsType: number;
+  // Missing without an OS/2 table or with conflicting bits
+  embedding?: EmbeddingLevel;
+  noSubsetting: boolean;
+  bitmapOnly: boolean;
+  // Name ID 13
+  description?: string;
+  // Name ID 14
+  url?: string;
+}
+
+export interface FaceMetadata {
+  localizedNames: string[];
+  // OS/2 usWidthClass: 1 ultra-condensed, 5 normal, 9 ultra-expanded
+  width: number;
+  monospace: boolean;
+  variable: boolean;
+  colour: boolean;
+  vendor?: string;
+  version?: string;
+  // e.g. "latin", "cyrillic", "han", "kana", "hangul"
+  scripts: string[];
+  features: string[];
 }
 
 export interface FontFamily {